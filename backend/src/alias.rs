@@ -1,7 +1,7 @@
 // Contains the logic for using the Treesitter library to parse a source file into an AST
-// and to query that AST for matching type aliases
+// and to match type aliases in that AST against a target type
 
-use regex::Regex;
+use regex::{Captures, Regex};
 use std::collections::HashMap;
 
 // Treesitter
@@ -11,201 +11,1306 @@ extern "C" {
     fn tree_sitter_haskell() -> Language;
 }
 
-use crate::types::{Match, Position, Range, RequestAlias, ResponseMatches, Target, Term};
+use crate::cpp::preprocess;
+use crate::family::{get_families, reduce_families};
+use crate::kind::{
+    check_kinds, get_kind_signatures, get_kinds, infer_kind, is_constraint_kind, kind_clash,
+};
+use crate::legality::{check_legality, get_extensions, Slot};
+use crate::literate::unliterate;
+use crate::near_miss::find_near_misses;
+use crate::score::{best_matches, score_match};
+use crate::types::{
+    Alias, Associativity, Context, Explanation, Failure, Fixity, Legality, Match, Param,
+    ParseError, Position, Range, RequestAlias, ResponseMatches, Target, TargetError, TargetMatches,
+    Term, TypeFamily, TypeTree,
+};
 
-pub fn alias_replacement(request: RequestAlias) -> ResponseMatches {
+// Text put in front of a type parsed on its own to make it the type of a signature
+pub(crate) const TARGET_PREFIX: &str = "afunc :: ";
+
+pub(crate) fn haskell_language() -> Language {
+    // The Treesitter grammar for Haskell
+    unsafe { tree_sitter_haskell() }
+}
+
+pub(crate) fn haskell_parser() -> Parser {
+    // A Treesitter parser for Haskell source
+    let mut parser = Parser::new();
+    parser.set_language(haskell_language()).unwrap();
+    parser
+}
+
+pub fn alias_replacement(request: RequestAlias) -> Result<ResponseMatches, TargetError> {
     // Public API function to convert a request into a response
     // Details on RequestAlias and ResponseMatches can be found in types.rs
+    for target_type in requested_targets(&request) {
+        validate_target(&target_type)?;
+    }
+    Ok(replace_with_imports(request, &[]))
+}
 
-    // Initialise treesitter
-    let mut parser = Parser::new();
-    let language = unsafe { tree_sitter_haskell() };
-    parser.set_language(language).unwrap();
-    let mut query_cursor = QueryCursor::new();
+pub(crate) fn requested_targets(request: &RequestAlias) -> Vec<String> {
+    // `target_type` comes first, followed by the other targets. A request without any targets
+    // has an empty one, which fails validation
+    let mut targets: Vec<String> = Vec::new();
+    for target_type in Some(&request.target_type)
+        .filter(|t| !t.is_empty() || request.targets.is_empty())
+        .into_iter()
+        .chain(request.targets.iter())
+    {
+        if !targets.contains(target_type) {
+            targets.push(target_type.clone());
+        }
+    }
+    targets
+}
+
+pub(crate) fn replace_with_imports(request: RequestAlias, imported: &[Alias]) -> ResponseMatches {
+    // Matches the target against the aliases of the source and those imported from other
+    // modules of a project
 
     // Source text
-    let source_bytes = request.source.as_bytes();
+    // Literate files are read as the Haskell they contain, which keeps every position the same
+    let source = if request.literate {
+        unliterate(&request.source)
+    } else {
+        request.source.clone()
+    };
+
+    // Conditional compilation can give several versions of the source. Matches, explanations
+    // and parse errors found in more than one version are only reported once
+    let targets = requested_targets(&request);
+    let mut results: Vec<TargetMatches> = targets
+        .iter()
+        .map(|t| TargetMatches {
+            target_type: t.clone(),
+            matches: Vec::new(),
+            explanations: Vec::new(),
+            near_misses: Vec::new(),
+        })
+        .collect();
+    let mut parse_errors: Vec<ParseError> = Vec::new();
+    for variant in preprocess(&source, &request.default_extensions, &request.defines) {
+        let (variant_results, variant_errors) =
+            find_matches(&request, &targets, &variant, imported);
+        for (result, variant_result) in results.iter_mut().zip(variant_results) {
+            for m in variant_result.matches {
+                if !result.matches.contains(&m) {
+                    result.matches.push(m);
+                }
+            }
+            for e in variant_result.explanations {
+                if !result.explanations.contains(&e) {
+                    result.explanations.push(e);
+                }
+            }
+            for n in variant_result.near_misses {
+                if !result.near_misses.contains(&n) {
+                    result.near_misses.push(n);
+                }
+            }
+        }
+        for e in variant_errors {
+            if !parse_errors.contains(&e) {
+                parse_errors.push(e);
+            }
+        }
+    }
+
+    if request.best {
+        for result in results.iter_mut() {
+            result.matches = best_matches(std::mem::take(&mut result.matches));
+        }
+    }
+
+    let matches = results
+        .iter()
+        .find(|r| r.target_type == request.target_type)
+        .map(|r| r.matches.clone())
+        .unwrap_or_default();
+
+    ResponseMatches {
+        echo_request: request,
+        matches,
+        results,
+        parse_errors,
+    }
+}
 
-    // Convert target to a Haskell type signature
-    let input_sig = format!("afunc :: {}", request.target_type);
+fn find_matches(
+    request: &RequestAlias,
+    targets: &[String],
+    source: &str,
+    imported: &[Alias],
+) -> (Vec<TargetMatches>, Vec<ParseError>) {
+    // Matches each target against the aliases of one version of the source
+    // The source is only parsed once for every target
 
-    // Convert type signature into a Treesitter query which matches type aliases with equivalent
-    // types to the target
-    let target_alias = create_target(input_sig.as_bytes());
+    // Initialise treesitter
+    let mut parser = haskell_parser();
+
+    let source_bytes = source.as_bytes();
+
+    // Infix type operators in both the target and the source use the fixities declared in the
+    // source
+    let fixities = get_fixities(source);
 
     // Create AST
-    let tree = parser.parse(&source_bytes, None).unwrap();
+    let tree = parser.parse(normalise_syntax(source), None).unwrap();
 
-    // Create and run query
-    let get_type_aliases = Query::new(language, &target_alias.query_str).unwrap();
-    let matches = query_cursor.matches(&get_type_aliases, tree.root_node(), source_bytes);
+    // Matching continues on the parts of the source which parsed
+    let parse_errors = get_parse_errors(&tree.root_node(), source_bytes);
 
-    // Filter Treesitter captures to remove terms which have inconsistent type parameters
-    let nodes = matches
-        .flat_map(|m| m.captures)
-        .map(|m| m.node)
-        .map(|n| {
-            (
-                n, // Need to pass through node for replacement later
-                check_variable_consistency(&target_alias.terms, get_terms(&n, source_bytes)),
-            )
-        })
-        .filter(|(_, r)| r.is_some());
-
-    // Process capture information from Treesitter into matches containing the required information
-    // for response
-    let matches: Vec<Match> = nodes
-        .map(|(n, r)| {
-            let matched = n
-                .parent()
-                .unwrap()
-                .utf8_text(source_bytes)
-                .unwrap()
-                .to_string();
-
-            // Location of matching type alias
-            let location = Range {
-                start: Position {
-                    row: n.start_position().row,
-                    col: n.start_position().column,
-                },
-                end: Position {
-                    row: n.end_position().row,
-                    col: n.end_position().column,
-                },
+    // Type families are evaluated in the right hand side of aliases where possible
+    let families = get_families(&tree.root_node(), source_bytes, &fixities);
+
+    // Find every type alias in the source and keep those which are equivalent to the target
+    // or which can replace part of the target's context
+    // Types substituted into an alias must have the kinds declared for its parameters
+    let kinds = get_kinds(source);
+
+    let mut aliases = get_aliases(&tree.root_node(), source_bytes, &fixities, &families);
+    aliases.extend(imported.iter().cloned());
+
+    let extensions = get_extensions(source, &request.default_extensions);
+    let unicode = uses_unicode_syntax(source);
+
+    let results = targets
+        .iter()
+        .map(|target_type| {
+            // Split the target into its context and the type under that context
+            let target = create_target(target_type, &fixities);
+
+            // The target is assumed to be written as the type of a top level signature
+            let annotate = |alias: &Alias, m: Match| Match {
+                score: score_match(alias, &m, &target.source, 0),
+                legality: check_legality(
+                    alias,
+                    &m.variable_map,
+                    &aliases,
+                    Context::Signature,
+                    Slot::Result,
+                    &target.body,
+                    &extensions,
+                ),
+                ..m
+            };
+
+            let matches: Vec<Match> = aliases
+                .iter()
+                .filter_map(|alias| Some(annotate(alias, match_alias(&target, alias, &kinds)?)))
+                .chain(aliases.iter().filter_map(|alias| {
+                    Some(annotate(
+                        alias,
+                        match_constraint_alias(&target, alias, &kinds)?,
+                    ))
+                }))
+                .collect();
+
+            // Replacements follow the syntax style of the source
+            let matches = matches
+                .into_iter()
+                .map(|m| Match {
+                    replaced_type: render_syntax(&m.replaced_type, unicode),
+                    ..flag_low_confidence(m, &parse_errors)
+                })
+                .collect();
+
+            let explanations = match &request.explain {
+                Some(name) => explain_alias(name, &target, &aliases, &kinds)
+                    .into_iter()
+                    .map(|e| Explanation {
+                        replaced_type: e.replaced_type.map(|r| render_syntax(&r, unicode)),
+                        ..e
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+
+            // Aliases which almost replace the target are kept apart from the matches
+            let near_misses = match request.near_miss {
+                Some(distance) => {
+                    find_near_misses(target_type, &target, &aliases, &kinds, distance)
+                }
+                None => Vec::new(),
             };
 
-            // A mapping from type variable to concrete type
-            let variable_map = r.unwrap();
-
-            // Extract name of alias
-            // There is almost certainly a better way to do this but this works for now
-            let temp_matched = matched.clone();
-            let re_name = Regex::new(r"type (.*)\s=").unwrap();
-            let mut replaced_type = re_name
-                .captures(&temp_matched)
-                .unwrap()
-                .get(1)
-                .unwrap()
-                .as_str()
-                .to_string();
-
-            // Substitute concrete types in for type variables
-            for (v, t) in variable_map.iter() {
-                let re_str = format!(r" {}(?P<after> |\z)", v);
-                let re_vars = Regex::new(&re_str).unwrap();
-                replaced_type = re_vars
-                    .replace_all(&replaced_type, format!(" {}$after", t))
-                    .to_string()
-            }
-
-            Match {
-                matched,
-                location,
-                variable_map,
-                replaced_type: replaced_type.to_string(),
+            TargetMatches {
+                target_type: target_type.clone(),
+                matches,
+                explanations,
+                near_misses,
             }
         })
         .collect();
 
-    ResponseMatches {
-        echo_request: request,
-        matches,
+    (results, parse_errors)
+}
+
+fn explain_alias(
+    name: &str,
+    target: &Target,
+    aliases: &[Alias],
+    kinds: &HashMap<String, String>,
+) -> Vec<Explanation> {
+    // Explains whether each alias called `name` matches the target, either as a type or as a
+    // constraint synonym, and if not where matching failed
+    // Imported aliases can be named with or without their qualifier
+    let named: Vec<&Alias> = aliases
+        .iter()
+        .filter(|a| a.name == name || a.name.rsplit_once('.').is_some_and(|(_, n)| n == name))
+        .collect();
+
+    if named.is_empty() {
+        return vec![Explanation {
+            alias: name.to_string(),
+            declaration: None,
+            location: None,
+            origin: None,
+            replaced_type: None,
+            failure: Some(Failure::NotInScope),
+        }];
     }
+
+    named
+        .into_iter()
+        .map(|alias| {
+            let (replaced_type, failure) = match try_match_alias(target, alias, kinds) {
+                Ok(m) => (Some(m.replaced_type), None),
+                Err(failure) => match match_constraint_alias(target, alias, kinds) {
+                    Some(m) => (Some(m.replaced_type), None),
+                    None => (None, Some(failure)),
+                },
+            };
+            Explanation {
+                alias: alias.name.clone(),
+                declaration: Some(alias.declaration.clone()),
+                location: Some(alias.location.clone()),
+                origin: alias.origin.clone(),
+                replaced_type,
+                failure,
+            }
+        })
+        .collect()
 }
 
-fn create_target(in_sig: &[u8]) -> Target {
-    // Generates a Treesitter query which matches type aliases equivalent to `in_sig`
+fn create_target(target_type: &str, fixities: &HashMap<String, Fixity>) -> Target {
+    // Splits `target_type` into quantifiers, a constraint context and the type under that
+    // context. Matching aliases must have the same shape and consistent terms as the type under
+    // the context
+    // Targets are validated before any source is matched
+    let sig_type = parse_type(target_type, fixities).unwrap();
+
+    target_from_tree(sig_type)
+}
+
+pub(crate) fn validate_target(target_type: &str) -> Result<(), TargetError> {
+    // Checks that `target_type` is a single well formed type, finding the column of the first
+    // problem and a hint for fixing it
+    let error = |byte: usize, message: String, hint: &str| TargetError {
+        target_type: target_type.to_string(),
+        message,
+        column: target_type.get(..byte).map_or(0, |t| t.chars().count()),
+        hint: hint.to_string(),
+    };
+
+    if target_type.trim().is_empty() {
+        return Err(error(
+            0,
+            "The target type is empty".to_string(),
+            "Give a type such as `Map Int String`",
+        ));
+    }
+
+    // Unbalanced brackets are reported where they were opened or closed
+    let mut open: Vec<(char, usize)> = Vec::new();
+    for (byte, c) in target_type.char_indices() {
+        match c {
+            '(' | '[' => open.push((c, byte)),
+            ')' | ']' => {
+                let opening = if c == ')' { '(' } else { '[' };
+                match open.pop() {
+                    Some((o, _)) if o == opening => {}
+                    Some((o, _)) => {
+                        return Err(error(
+                            byte,
+                            format!("`{}` doesn't close the `{}` before it", c, o),
+                            "Check that brackets are closed in the order they were opened",
+                        ))
+                    }
+                    None => {
+                        return Err(error(
+                            byte,
+                            format!("Unmatched `{}`", c),
+                            "Remove it or add the bracket it closes",
+                        ))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((o, byte)) = open.pop() {
+        return Err(error(
+            byte,
+            format!("Unclosed `{}`", o),
+            "Add the bracket which closes it",
+        ));
+    }
+
+    // Arrows need a type on both sides
+    let normalised = normalise_syntax(target_type);
+    let trimmed = normalised.trim_end();
+    for arrow in ["->", "=>"] {
+        if trimmed.ends_with(arrow) {
+            return Err(error(
+                trimmed.len() - arrow.len(),
+                format!("Missing the type after `{}`", arrow),
+                "Give the type after the arrow, as in `Int -> Bool`",
+            ));
+        }
+        if normalised.trim_start().starts_with(arrow) {
+            let byte = normalised.len() - normalised.trim_start().len();
+            return Err(error(
+                byte,
+                format!("Missing the type before `{}`", arrow),
+                "Give the type before the arrow, as in `Int -> Bool`",
+            ));
+        }
+    }
+
+    // Anything else the grammar rejects is reported at its first parse error
+    // The target is parsed as the type of a signature as in `parse_type`
+    let in_sig = format!("{}{}", TARGET_PREFIX, target_type);
+    let mut parser = haskell_parser();
+    let tree = parser.parse(normalise_syntax(&in_sig), None).unwrap();
+
+    let hint = "Write the target as the type of a signature, such as `Ord k => k -> Map k v`";
+    if let Some(e) = get_parse_errors(&tree.root_node(), in_sig.as_bytes()).first() {
+        let line_start: usize = in_sig
+            .split('\n')
+            .take(e.location.start.row)
+            .map(|l| l.len() + 1)
+            .sum();
+        let byte = (line_start + e.location.start.col).saturating_sub(TARGET_PREFIX.len());
+        return Err(error(byte, e.message.clone(), hint));
+    }
+    if parse_type(target_type, &HashMap::new()).is_none() {
+        return Err(error(0, "The target isn't a type".to_string(), hint));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn target_from_tree(sig_type: TypeTree) -> Target {
+    // Builds a target from a type which has already been parsed
+
+    // Reconstruct input signature
+    let source = sig_type.text.clone();
+
+    // Contexts are matched separately so only the type under the context determines the shape
+    let (quantified, constraints, body) = split_context(sig_type);
+
+    Target {
+        shape: get_shape(&body),
+        source,
+        terms: get_terms(&body),
+        quantified,
+        constraints,
+        body,
+    }
+}
+
+pub(crate) fn parse_type(text: &str, fixities: &HashMap<String, Fixity>) -> Option<TypeTree> {
+    // Parses a type on its own into a TypeTree
 
     // Initialise Treesitter
-    let mut parser = Parser::new();
-    let language = unsafe { tree_sitter_haskell() };
-    parser.set_language(language).unwrap();
+    let mut parser = haskell_parser();
     let mut query_cursor = QueryCursor::new();
 
     // Create Treesitter tree of input type
-    // Note: "afunc :: " has been prepended to the target to make this possible
-    let sig_tree = parser.parse(in_sig, None).unwrap();
+    // Note: TARGET_PREFIX is prepended to the type to make this possible
+    let in_sig = format!("{}{}", TARGET_PREFIX, text);
+    let sig_tree = parser.parse(normalise_syntax(&in_sig), None).unwrap();
 
     // Search for a type signature in the tree
-    let sig_query = Query::new(language, &"(signature) @sig").unwrap();
+    let sig_query = Query::new(haskell_language(), "(signature) @sig").unwrap();
 
-    // Convert to a flat list of types. A matching alias must have the same structure of types as
-    // this list
-    let sig_matches = query_cursor.matches(&sig_query, sig_tree.root_node(), in_sig);
-    let sig_nodes: Vec<TSNode> = sig_matches
-        .flat_map(|m| m.captures)
-        .map(|m| {
-            m.node
-                .child_by_field_name("type")
-                .unwrap()
-                .next_sibling()
-                .unwrap()
+    let mut sig_matches = query_cursor.matches(&sig_query, sig_tree.root_node(), in_sig.as_bytes());
+    let sig_node = sig_matches
+        .next()?
+        .captures
+        .first()?
+        .node
+        .child_by_field_name("type")?
+        .next_sibling()?;
+
+    Some(resolve_fixity(
+        to_type_tree(&sig_node, in_sig.as_bytes()),
+        fixities,
+    ))
+}
+
+pub(crate) fn normalise_syntax(text: &str) -> String {
+    // Rewrites syntax the Haskell grammar doesn't understand before parsing
+    // Unicode arrows become their ASCII equivalents, multiplicity annotations are replaced with
+    // spaces and `⊸` becomes `->`. Byte offsets are unchanged which lets the original text be
+    // used when building TypeTrees
+    let ascii = text
+        .replace('→', "-> ")
+        .replace('⇒', "=> ")
+        .replace('∷', ":: ")
+        .replace('⊸', "-> ");
+
+    let re_multiplicity = Regex::new(r"%\s*\w+(\s*->)").unwrap();
+    re_multiplicity
+        .replace_all(&ascii, |c: &Captures| {
+            format!("{}{}", " ".repeat(c[0].len() - c[1].len()), &c[1])
         })
+        .to_string()
+}
+
+pub(crate) fn uses_unicode_syntax(source: &str) -> bool {
+    // Checks if a file is written using the symbols of UnicodeSyntax, outside of its comments
+    // and string literals
+    strip_comments(source).contains(['→', '⇒', '∷', '∀'])
+}
+
+pub(crate) fn render_syntax(text: &str, unicode: bool) -> String {
+    // Renders a replacement in either ASCII or Unicode syntax
+    // Only whole tokens are rewritten, leaving operators such as `:->` and type level strings
+    // such as `"a->b"` alone
+    let re_token =
+        Regex::new(r#""(?:[^"\\]|\\.)*"|[A-Za-z_][\w'.]*|[!#$%&*+./<=>?@\\^|~:\-→⇒∷∀]+"#).unwrap();
+
+    re_token
+        .replace_all(text, |c: &Captures| {
+            let token = &c[0];
+            let rendered = match (unicode, token) {
+                (true, "forall") => "∀",
+                (true, "->") => "→",
+                (true, "=>") => "⇒",
+                (true, "::") => "∷",
+                (false, "∀") => "forall",
+                (false, "→") => "->",
+                (false, "⇒") => "=>",
+                (false, "∷") => "::",
+                _ => token,
+            };
+            rendered.to_string()
+        })
+        .to_string()
+}
+
+pub(crate) fn strip_comments(text: &str) -> String {
+    // Blanks the comments and string literals of some source, keeping every line and byte
+    // offset the same
+    let mut stripped = String::with_capacity(text.len());
+    let mut end = 0;
+    for (span, _) in comments_and_strings(text) {
+        stripped.push_str(&text[end..span.start]);
+        for c in text[span.clone()].chars() {
+            match c {
+                '\n' => stripped.push('\n'),
+                c => stripped.push_str(&" ".repeat(c.len_utf8())),
+            }
+        }
+        end = span.end;
+    }
+    stripped.push_str(&text[end..]);
+    stripped
+}
+
+pub(crate) fn contains_comment(text: &str) -> bool {
+    comments_and_strings(text)
+        .iter()
+        .any(|(_, comment)| *comment)
+}
+
+fn comments_and_strings(text: &str) -> Vec<(std::ops::Range<usize>, bool)> {
+    // Finds the byte ranges of the comments and string literals of some source, along with
+    // whether each is a comment. Pragmas such as `{-# SOURCE #-}` aren't comments
+    // Dashes only start a comment when they aren't part of a longer operator such as `-->`
+    let re_token = Regex::new(
+        r#"(?s)\{-.*?-\}|"(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.)'|[A-Za-z_][\w']*|[!#$%&*+./<=>?@\\^|~:-]+"#,
+    )
+    .unwrap();
+
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for m in re_token.find_iter(text) {
+        if m.start() < end {
+            continue;
+        }
+        end = m.end();
+
+        let token = m.as_str();
+        if token.len() > 1 && token.chars().all(|c| c == '-') {
+            end = text[m.start()..]
+                .find('\n')
+                .map_or(text.len(), |i| m.start() + i);
+            spans.push((m.start()..end, true));
+        } else if token.starts_with("{-") && !token.starts_with("{-#") {
+            spans.push((m.range(), true));
+        } else if token.starts_with(['"', '\'']) {
+            spans.push((m.range(), false));
+        }
+    }
+    spans
+}
+
+pub(crate) fn get_parse_errors(node: &TSNode, source: &[u8]) -> Vec<ParseError> {
+    // Finds the outermost ERROR nodes and every MISSING node below `node`
+    if node.is_missing() {
+        return vec![ParseError {
+            message: format!("Missing {}", node.kind()),
+            location: node_range(node),
+        }];
+    }
+    if node.is_error() {
+        let text = node.utf8_text(source).unwrap_or_default();
+        let first_line = text.lines().next().unwrap_or_default().trim();
+        return vec![ParseError {
+            message: format!("Unexpected syntax `{}`", first_line),
+            location: node_range(node),
+        }];
+    }
+    if !node.has_error() {
+        return Vec::new();
+    }
+
+    let mut cursor = node.walk();
+    let errors = node
+        .children(&mut cursor)
+        .flat_map(|n| get_parse_errors(&n, source))
         .collect();
+    errors
+}
 
-    // Generate query string matching a type or type variable wherever either appear
-    let query_str_pre = format!("{}{}{}", "(type_alias ", sig_nodes[0].to_sexp(), " @alias)");
-    let re = Regex::new(r"\(type_variable\)|\(type\)").unwrap();
-    let query_str = re
-        .replace_all(&query_str_pre, "[(type) (type_variable)]")
-        .to_string();
+pub(crate) fn flag_low_confidence(m: Match, errors: &[ParseError]) -> Match {
+    // Matches involving code which overlaps a parse error may be wrong
+    // An imported alias is located in a different file so only the occurrence is checked
+    let overlapping = errors.iter().any(|e| {
+        (m.origin.is_none() && overlaps(&e.location, &m.location))
+            || m.occurrence
+                .as_ref()
+                .is_some_and(|o| overlaps(&e.location, &o.location))
+    });
 
-    // Reconstruct input signature
-    let source = sig_nodes[0].utf8_text(in_sig).unwrap().to_string();
+    Match {
+        low_confidence: m.low_confidence || overlapping,
+        ..m
+    }
+}
 
-    // List of terms used to check variable consistency
-    let terms = get_terms(&sig_nodes[0], in_sig);
+fn overlaps(a: &Range, b: &Range) -> bool {
+    let start = |r: &Range| (r.start.row, r.start.col);
+    let end = |r: &Range| (r.end.row, r.end.col);
+    start(a) <= end(b) && start(b) <= end(a)
+}
 
-    Target {
-        query_str,
-        source,
-        terms,
+pub(crate) fn find_nodes<'a>(node: &TSNode<'a>, kinds: &[&str]) -> Vec<TSNode<'a>> {
+    // Finds the outermost nodes below `node` with any of the given kinds
+    if kinds.contains(&node.kind()) {
+        return vec![*node];
     }
+
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .flat_map(|n| find_nodes(&n, kinds))
+        .collect()
 }
 
-fn get_terms<'a>(node: &TSNode, source: &'a [u8]) -> Vec<Term> {
-    // Transform a subtree from a treesitter node into a flat list of terms
-    // A term is either a concrete type or a variable which will need to be
-    // checked for consistency
+pub(crate) fn is_associated(node: &TSNode) -> bool {
+    // Checks if a declaration belongs to a class or instance
+    let mut parent = node.parent();
+    while let Some(p) = parent {
+        if p.kind().starts_with("class") || p.kind().starts_with("instance") {
+            return true;
+        }
+        parent = p.parent();
+    }
+    false
+}
+
+pub(crate) fn get_aliases(
+    root: &TSNode,
+    source: &[u8],
+    fixities: &HashMap<String, Fixity>,
+    families: &[TypeFamily],
+) -> Vec<Alias> {
+    // Collects every type alias declared below `root`
+    // Type family equations and associated types look like aliases but can't be suggested
 
     // Initialise Treesitter
-    let mut cursor = node.walk();
     let mut query_cursor = QueryCursor::new();
-    let language = unsafe { tree_sitter_haskell() };
 
-    // Query for all type nodes below the parent
-    let type_query = "(type_name) @type";
-    let get_types = Query::new(language, &type_query).unwrap();
-    let type_matches = query_cursor.matches(&get_types, *node, source);
+    let alias_query = Query::new(haskell_language(), "(type_alias) @alias").unwrap();
+    let alias_matches = query_cursor.matches(&alias_query, *root, source);
+
+    // Parameters without a kind annotation take their kind from a standalone kind signature
+    let signatures = get_kind_signatures(std::str::from_utf8(source).unwrap());
 
-    // Flatten and convert into Terms and return
-    type_matches
+    alias_matches
         .flat_map(|m| m.captures)
-        .map(|m| {
-            if m.node
-                .children(&mut cursor)
-                .any(|n| n.kind() == "type_variable")
-            {
-                Term::Variable(m.node.utf8_text(source).unwrap().to_string())
-            } else {
-                Term::Type(m.node.utf8_text(source).unwrap().to_string())
+        .filter(|m| !is_associated(&m.node))
+        .filter_map(|m| {
+            let declaration = m.node.utf8_text(source).unwrap().to_string();
+            if declaration.starts_with("type family") || declaration.starts_with("type instance") {
+                return None;
             }
+
+            // The aliased type is the first named node after `=`
+            let mut cursor = m.node.walk();
+            let equals = m.node.children(&mut cursor).find(|n| n.kind() == "=")?;
+            let rhs = equals.next_named_sibling()?;
+
+            // The head of the alias is everything between `type` and `=`
+            let head = &declaration[..equals.start_byte() - m.node.start_byte()];
+            let (name, mut params, infix) = parse_head(head.trim_start_matches("type"));
+            if let Some(kinds) = signatures.get(&name) {
+                for (param, kind) in params.iter_mut().zip(kinds.iter()) {
+                    if param.kind.is_none() {
+                        param.kind = Some(kind.to_string());
+                    }
+                }
+            }
+
+            let rhs_type = resolve_fixity(to_type_tree(&rhs, source), fixities);
+            let rhs_type = reduce_families(rhs_type, families, fixities);
+            let (quantified, constraints, body) = split_context(rhs_type);
+
+            Some(Alias {
+                origin: None,
+                name,
+                params,
+                infix,
+                location: node_range(&rhs),
+                quantified,
+                constraints,
+                rhs: body,
+                declaration,
+            })
         })
         .collect()
 }
 
-fn check_variable_consistency(
-    target_terms: &Vec<Term>,
-    candidate_terms: Vec<Term>,
+pub(crate) fn match_alias(
+    target: &Target,
+    alias: &Alias,
+    kinds: &HashMap<String, String>,
+) -> Option<Match> {
+    // Checks if `alias` can replace the target and, if so, builds the replacement
+    try_match_alias(target, alias, kinds).ok()
+}
+
+pub(crate) fn try_match_alias(
+    target: &Target,
+    alias: &Alias,
+    kinds: &HashMap<String, String>,
+) -> Result<Match, Failure> {
+    // Builds the replacement of the target by `alias`, or finds the step at which it can't
+    // replace the target
+
+    // Structure of types must be identical
+    if get_shape(&alias.rhs) != target.shape {
+        let (alias_part, target_part) = structure_difference(&alias.rhs, &target.body);
+        return Err(Failure::StructureMismatch {
+            alias: alias_part.text.clone(),
+            target: target_part.text.clone(),
+        });
+    }
+
+    // Filter aliases which have inconsistent type parameters
+    let variable_map =
+        check_variable_consistency(&target.terms, get_terms(&alias.rhs), HashMap::new())?;
+    if let Some((param, declared, inferred)) = kind_clash(&alias.params, &variable_map, kinds) {
+        return Err(Failure::KindClash {
+            bound: variable_map[&param].clone(),
+            param,
+            declared,
+            inferred,
+        });
+    }
+
+    // Variables quantified inside the alias must correspond to distinct type variables of the
+    // target which aren't also bound to a parameter of the alias
+    let scope_problem = |variable: &str, reason: String| Failure::ScopeProblem {
+        variable: variable.to_string(),
+        reason,
+    };
+    let mut captured: Vec<String> = Vec::new();
+    for v in alias.quantified.iter() {
+        if let Some(t) = variable_map.get(v) {
+            if !target.terms.contains(&Term::Variable(t.to_string())) {
+                return Err(scope_problem(
+                    v,
+                    format!("is quantified in the alias but bound to `{}`", t),
+                ));
+            }
+            if captured.contains(t) {
+                return Err(scope_problem(
+                    v,
+                    format!("is bound to `{}` like another quantified variable", t),
+                ));
+            }
+            captured.push(t.to_string());
+        }
+    }
+    let escaping = variable_map
+        .iter()
+        .find(|(v, t)| !alias.quantified.contains(v) && captured.contains(t));
+    if let Some((v, t)) = escaping {
+        return Err(scope_problem(
+            v,
+            format!(
+                "is a parameter bound to `{}`, which the alias quantifies",
+                t
+            ),
+        ));
+    }
+
+    // Every constraint of the alias must appear in the target's context. Any constraints left
+    // over stay in front of the replacement
+    let mut remaining: Vec<String> = target
+        .constraints
+        .iter()
+        .map(|c| normalise_whitespace(&c.text))
+        .collect();
+    for constraint in alias.constraints.iter() {
+        let constraint = normalise_whitespace(&substitute(constraint, &variable_map));
+        let i =
+            remaining
+                .iter()
+                .position(|c| *c == constraint)
+                .ok_or(Failure::MissingConstraint {
+                    constraint: constraint.clone(),
+                })?;
+        remaining.remove(i);
+    }
+    for c in remaining.iter() {
+        if let Some(t) = captured.iter().find(|t| mentions(c, t)) {
+            return Err(scope_problem(
+                t,
+                format!(
+                    "is mentioned by the constraint `{}` but quantified in the alias",
+                    c
+                ),
+            ));
+        }
+    }
+
+    let quantified: Vec<String> = target
+        .quantified
+        .iter()
+        .filter(|v| !captured.contains(v))
+        .cloned()
+        .collect();
+
+    let replaced_type = add_context(&quantified, &remaining, &apply_head(alias, &variable_map));
+
+    Ok(Match {
+        matched: alias.declaration.clone(),
+        location: alias.location.clone(),
+        variable_map,
+        replaced_type,
+        occurrence: None,
+        legality: Legality::Legal,
+        origin: alias.origin.clone(),
+        low_confidence: false,
+        score: 0.0,
+    })
+}
+
+pub(crate) fn match_constraint_alias(
+    target: &Target,
+    alias: &Alias,
+    kinds: &HashMap<String, String>,
+) -> Option<Match> {
+    // Checks if `alias` is a constraint synonym whose constraints all appear in the target's
+    // context. The order of constraints doesn't matter
+
+    if !alias.quantified.is_empty() || !alias.constraints.is_empty() {
+        return None;
+    }
+
+    // A tuple of constraints or a single class applied to some types
+    let elements = match alias.rhs.kind.as_str() {
+        "type_tuple" => alias.rhs.children.clone(),
+        "type_apply" => match get_terms(&alias.rhs.children[0])[..] {
+            [Term::Type(_)] => vec![alias.rhs.clone()],
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    // Tuples of types and applied type constructors aren't constraints. The alias must have a
+    // kind signature returning `Constraint` or a class at the head of every element
+    let declared = kinds
+        .get(&alias.name)
+        .is_some_and(|k| is_constraint_kind(k));
+    if !declared && !elements.iter().all(|e| is_class_constraint(e, kinds)) {
+        return None;
+    }
+
+    let mut used = Vec::new();
+    let variable_map =
+        assign_constraints(&elements, &target.constraints, &mut used, HashMap::new())?;
+    if !check_kinds(&alias.params, &variable_map, kinds) {
+        return None;
+    }
+
+    // Replace the matched constraints with the synonym where the first of them appeared
+    let first = *used.iter().min()?;
+    let constraints: Vec<String> = target
+        .constraints
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| {
+            if i == first {
+                Some(apply_head(alias, &variable_map))
+            } else if used.contains(&i) {
+                None
+            } else {
+                Some(normalise_whitespace(&c.text))
+            }
+        })
+        .collect();
+
+    Some(Match {
+        matched: alias.declaration.clone(),
+        location: alias.location.clone(),
+        replaced_type: add_context(&target.quantified, &constraints, &target.body.text),
+        variable_map,
+        occurrence: None,
+        legality: Legality::Legal,
+        origin: alias.origin.clone(),
+        low_confidence: false,
+        score: 0.0,
+    })
+}
+
+fn is_class_constraint(tree: &TypeTree, kinds: &HashMap<String, String>) -> bool {
+    // Checks if a constraint is a class declared in the source or a common class applied to
+    // some types
+    let head = match tree.kind.as_str() {
+        "type_apply" => &tree.children[0],
+        _ => tree,
+    };
+    match &get_terms(head)[..] {
+        [Term::Type(name)] => infer_kind(name, kinds).is_some_and(|k| is_constraint_kind(&k)),
+        _ => false,
+    }
+}
+
+fn assign_constraints(
+    elements: &[TypeTree],
+    constraints: &[TypeTree],
+    used: &mut Vec<usize>,
+    variable_map: HashMap<String, String>,
 ) -> Option<HashMap<String, String>> {
-    // Checks if the two lists of terms are consistent with eachother
-    // If they are consistent, return a mapping from candidate type variables to target concrete
-    // types
-    // If not, return None
+    // Finds a distinct constraint of the target for every element of a constraint synonym
+    // such that all variables are bound consistently. Indices of the constraints used are
+    // recorded in `used`
+    let (element, rest) = match elements.split_first() {
+        None => return Some(variable_map),
+        Some(split) => split,
+    };
+
+    for (i, constraint) in constraints.iter().enumerate() {
+        if used.contains(&i) || get_shape(element) != get_shape(constraint) {
+            continue;
+        }
+
+        let extended = check_variable_consistency(
+            &get_terms(constraint),
+            get_terms(element),
+            variable_map.clone(),
+        );
+        if let Ok(extended) = extended {
+            used.push(i);
+            if let Some(result) = assign_constraints(rest, constraints, used, extended) {
+                return Some(result);
+            }
+            used.pop();
+        }
+    }
+
+    None
+}
+
+pub(crate) fn to_type_tree(node: &TSNode, source: &[u8]) -> TypeTree {
+    // Copies the named nodes of a Treesitter subtree into an owned TypeTree
+    // The operator of an infix type is always kept, even if the grammar leaves it unnamed
+    let mut cursor = node.walk();
+    let mut children: Vec<TypeTree> = node
+        .children(&mut cursor)
+        .filter(|n| n.kind() != "comment")
+        .filter(|n| {
+            n.is_named() || (node.kind() == "type_infix" && !["(", ")"].contains(&n.kind()))
+        })
+        .map(|n| to_type_tree(&n, source))
+        .collect();
+
+    if node.kind() == "type_infix" && children.len() == 3 {
+        let op = &mut children[1];
+        op.kind = "type_operator".to_string();
+        op.children.clear();
+    }
+
+    // Arrows with a multiplicity other than `Many` keep it between their operands
+    if node.kind() == "fun" && children.len() == 2 {
+        if let Some(multiplicity) = get_multiplicity(node, &children[0], source) {
+            children.insert(1, multiplicity);
+        }
+    }
+
+    let mut kind = node.kind().to_string();
+
+    // Type level strings and numbers are atoms which type variables can be bound to
+    if ["type_literal", "string", "integer", "char"].contains(&node.kind()) {
+        kind = "type_literal".to_string();
+        children.clear();
+    }
+
+    // The tick on a promoted constructor is optional but a promoted list or tuple is a
+    // different type to the unpromoted one
+    let ticked = node.child(0).is_some_and(|c| c.kind() == "'");
+    if node.kind() == "promoted" || ticked {
+        if let [inner] = &children[..] {
+            if inner.kind == "type_name" {
+                return inner.clone();
+            }
+        }
+        if ticked {
+            kind = format!("promoted_{}", kind);
+        }
+    }
+
+    TypeTree {
+        kind,
+        text: node.utf8_text(source).unwrap().to_string(),
+        children,
+        location: node_range(node),
+        start_byte: node.start_byte(),
+        end_byte: node.end_byte(),
+    }
+}
+
+fn get_multiplicity(node: &TSNode, domain: &TypeTree, source: &[u8]) -> Option<TypeTree> {
+    // Reads the multiplicity of a function arrow from the original source text
+    let mut cursor = node.walk();
+    let arrow = node.children(&mut cursor).find(|n| n.kind() == "->")?;
+
+    let (start_byte, end_byte) = if source[arrow.start_byte()..].starts_with("⊸".as_bytes()) {
+        (arrow.start_byte(), arrow.start_byte() + "⊸".len())
+    } else {
+        let gap = std::str::from_utf8(&source[domain.end_byte..arrow.start_byte()]).ok()?;
+        let re_multiplicity = Regex::new(r"%\s*(\w+)").unwrap();
+        let multiplicity = re_multiplicity.captures(gap)?.get(1)?;
+        if multiplicity.as_str() == "Many" {
+            return None;
+        }
+        (
+            domain.end_byte + multiplicity.start(),
+            domain.end_byte + multiplicity.end(),
+        )
+    };
+
+    // The multiplicity may be on an earlier line than its arrow, so its position is counted back
+    // from the arrow's
+    let position = |byte: usize| {
+        let between = &source[byte..arrow.start_byte()];
+        let line_start = source[..byte]
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+        Position {
+            row: arrow.start_position().row - between.iter().filter(|b| **b == b'\n').count(),
+            col: byte - line_start,
+        }
+    };
+    Some(TypeTree {
+        kind: "multiplicity".to_string(),
+        text: std::str::from_utf8(&source[start_byte..end_byte])
+            .unwrap()
+            .to_string(),
+        children: Vec::new(),
+        location: Range {
+            start: position(start_byte),
+            end: position(end_byte),
+        },
+        start_byte,
+        end_byte,
+    })
+}
+
+pub(crate) fn get_fixities(source: &str) -> HashMap<String, Fixity> {
+    // Reads `infixl`, `infixr` and `infix` declarations from the source, outside of comments
+    let re_fixity = Regex::new(r"(?m)^\s*(infixl|infixr|infix)\s+(\d)?\s*(.+)$").unwrap();
+
+    // Servant's combinators are rarely redeclared so their fixities are known in advance
+    let mut fixities: HashMap<String, Fixity> = [(":>", 4), (":<|>", 3)]
+        .iter()
+        .map(|(op, precedence)| {
+            (
+                op.to_string(),
+                Fixity {
+                    associativity: Associativity::Right,
+                    precedence: *precedence,
+                },
+            )
+        })
+        .collect();
+
+    for declaration in re_fixity.captures_iter(&strip_comments(source)) {
+        let associativity = match &declaration[1] {
+            "infixl" => Associativity::Left,
+            "infixr" => Associativity::Right,
+            _ => Associativity::None,
+        };
+        let precedence = declaration
+            .get(2)
+            .map_or(9, |p| p.as_str().parse().unwrap());
+
+        for op in declaration[3].split(',') {
+            fixities.insert(
+                op.trim().trim_matches('`').to_string(),
+                Fixity {
+                    associativity: associativity.clone(),
+                    precedence,
+                },
+            );
+        }
+    }
+
+    fixities
+}
+
+pub(crate) fn resolve_fixity(tree: TypeTree, fixities: &HashMap<String, Fixity>) -> TypeTree {
+    // Treesitter doesn't know the fixities of type operators so chains of infix types are
+    // flattened and re-associated using the declared fixities
+    // Operators without a declaration default to `infixl 9` as in GHC
+    if tree.kind != "type_infix" || tree.children.len() != 3 {
+        let children = tree
+            .children
+            .into_iter()
+            .map(|c| resolve_fixity(c, fixities))
+            .collect();
+        return TypeTree { children, ..tree };
+    }
+
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    flatten_infix(tree.clone(), &mut operands, &mut operators);
+
+    let fixity = |op: &TypeTree| {
+        fixities
+            .get(op.text.trim_matches('`'))
+            .cloned()
+            .unwrap_or(Fixity {
+                associativity: Associativity::Left,
+                precedence: 9,
+            })
+    };
 
-    // Initialise HashMap
-    let mut variable_map: HashMap<String, String> = HashMap::new();
+    // Shunting yard
+    let mut operands = operands.into_iter().map(|c| resolve_fixity(c, fixities));
+    let mut output = vec![operands.next().unwrap()];
+    let mut stack: Vec<TypeTree> = Vec::new();
+
+    for (op, operand) in operators.into_iter().zip(operands) {
+        while let Some(top) = stack.last() {
+            let (top_fixity, op_fixity) = (fixity(top), fixity(&op));
+            if top_fixity.precedence > op_fixity.precedence
+                || (top_fixity.precedence == op_fixity.precedence
+                    && op_fixity.associativity != Associativity::Right)
+            {
+                let top = stack.pop().unwrap();
+                reduce_infix(&tree, &mut output, top);
+            } else {
+                break;
+            }
+        }
+        stack.push(op);
+        output.push(operand);
+    }
+    while let Some(top) = stack.pop() {
+        reduce_infix(&tree, &mut output, top);
+    }
+
+    output.pop().unwrap()
+}
+
+fn flatten_infix(tree: TypeTree, operands: &mut Vec<TypeTree>, operators: &mut Vec<TypeTree>) {
+    // Collects the operands and operators of a chain of infix types in order
+    // Parentheses end the chain
+    if tree.kind == "type_infix" && tree.children.len() == 3 {
+        let mut children = tree.children.into_iter();
+        flatten_infix(children.next().unwrap(), operands, operators);
+        operators.push(children.next().unwrap());
+        flatten_infix(children.next().unwrap(), operands, operators);
+    } else {
+        operands.push(tree);
+    }
+}
+
+fn reduce_infix(chain: &TypeTree, output: &mut Vec<TypeTree>, op: TypeTree) {
+    // Combines the top two operands on the output stack using `op`
+    let right = output.pop().unwrap();
+    let left = output.pop().unwrap();
+
+    output.push(TypeTree {
+        kind: "type_infix".to_string(),
+        text: chain.text[left.start_byte - chain.start_byte..right.end_byte - chain.start_byte]
+            .to_string(),
+        location: Range {
+            start: left.location.start.clone(),
+            end: right.location.end.clone(),
+        },
+        start_byte: left.start_byte,
+        end_byte: right.end_byte,
+        children: vec![left, op, right],
+    });
+}
+
+fn split_context(tree: TypeTree) -> (Vec<String>, Vec<TypeTree>, TypeTree) {
+    // Strips any `forall` and constraint contexts from the front of a type
+    // Returns the quantified variables, the constraints and the remaining type
+    let mut quantified = Vec::new();
+    let mut constraints = Vec::new();
+    let mut body = tree;
+
+    while (body.kind == "forall" || body.kind == "context") && body.children.len() > 1 {
+        let mut children = body.children;
+        let inner = children.pop().unwrap();
+
+        for child in children {
+            if body.kind == "forall" {
+                quantified.extend(
+                    find_kind(&child, "type_variable")
+                        .iter()
+                        .map(|v| v.text.clone()),
+                );
+            } else {
+                let found = find_kind(&child, "constraint");
+                if found.is_empty() {
+                    constraints.push(child);
+                } else {
+                    constraints.extend(found.into_iter().map(constraint_as_type));
+                }
+            }
+        }
+
+        body = inner;
+    }
+
+    (quantified, constraints, body)
+}
+
+fn constraint_as_type(tree: TypeTree) -> TypeTree {
+    // Constraints are parsed with their own node kinds. Renaming them to the equivalent type
+    // kinds lets constraints be compared against the right hand side of constraint synonyms
+    let kind = match tree.kind.as_str() {
+        "constraint" => "type_apply".to_string(),
+        "class_name" => "type_name".to_string(),
+        _ => tree.kind,
+    };
+
+    TypeTree {
+        kind,
+        children: tree.children.into_iter().map(constraint_as_type).collect(),
+        ..tree
+    }
+}
+
+fn find_kind(tree: &TypeTree, kind: &str) -> Vec<TypeTree> {
+    // Finds the outermost nodes of a given kind in a tree
+    if tree.kind == kind {
+        vec![tree.clone()]
+    } else {
+        tree.children
+            .iter()
+            .flat_map(|c| find_kind(c, kind))
+            .collect()
+    }
+}
+
+fn structure_difference<'a>(
+    alias: &'a TypeTree,
+    target: &'a TypeTree,
+) -> (&'a TypeTree, &'a TypeTree) {
+    // Finds the outermost parts of an alias and a target which have different shapes
+    let node = |t: &TypeTree| match t.kind.as_str() {
+        "type_literal" => "type_name".to_string(),
+        kind => kind.to_string(),
+    };
+    if node(alias) != node(target) || alias.children.len() != target.children.len() {
+        return (alias, target);
+    }
+
+    alias
+        .children
+        .iter()
+        .zip(target.children.iter())
+        .find(|(a, t)| get_shape(a) != get_shape(t))
+        .map_or((alias, target), |(a, t)| structure_difference(a, t))
+}
+
+pub(crate) fn get_shape(tree: &TypeTree) -> String {
+    // Builds an S-expression of a type in which all type names are equivalent
+    // Two types with equal shapes only differ in the names used at each term
+    if tree.kind == "type_name" || tree.kind == "type_literal" {
+        "(type_name)".to_string()
+    } else if tree.children.is_empty() {
+        format!("({})", tree.kind)
+    } else {
+        let children: Vec<String> = tree.children.iter().map(get_shape).collect();
+        format!("({} {})", tree.kind, children.join(" "))
+    }
+}
+
+pub(crate) fn get_terms(tree: &TypeTree) -> Vec<Term> {
+    // Transform a type tree into a flat list of terms
+    // A term is either a concrete type or a variable which will need to be
+    // checked for consistency
+    if tree.kind == "type_variable"
+        || (tree.kind == "type_name" && tree.children.iter().any(|n| n.kind == "type_variable"))
+    {
+        vec![Term::Variable(tree.text.clone())]
+    } else if tree.kind == "multiplicity" {
+        // `%1` and `⊸` are sugar for `%One`
+        match tree.text.as_str() {
+            "1" | "⊸" => vec![Term::Type("One".to_string())],
+            m if m.starts_with(char::is_lowercase) => vec![Term::Variable(m.to_string())],
+            m => vec![Term::Type(m.to_string())],
+        }
+    } else if tree.kind == "type_literal" {
+        vec![Term::Literal(tree.text.clone())]
+    } else if tree.kind == "type_operator" {
+        // `Either` and a ticked `Either` are the same operator
+        vec![Term::Type(tree.text.trim_matches('`').to_string())]
+    } else if tree.kind == "type_name" || tree.children.is_empty() {
+        vec![Term::Type(tree.text.clone())]
+    } else {
+        tree.children.iter().flat_map(get_terms).collect()
+    }
+}
+
+fn check_variable_consistency(
+    target_terms: &[Term],
+    candidate_terms: Vec<Term>,
+    mut variable_map: HashMap<String, String>,
+) -> Result<HashMap<String, String>, Failure> {
+    // Checks if the two lists of terms are consistent with eachother and with any variables
+    // already assigned in `variable_map`
+    // If they are consistent, return a mapping from candidate type variables to target types
+    // If not, return the terms which are inconsistent
+    let mismatch = |alias: &str, target: &str| Failure::ConcreteMismatch {
+        alias: alias.to_string(),
+        target: target.to_string(),
+    };
 
     let pairs = candidate_terms.iter().zip(target_terms.iter());
 
@@ -214,28 +1319,168 @@ fn check_variable_consistency(
             // Check if a variable has already been assigned to a term
             // If not, assign a mapping
             // If so ensure the variable matches the type otherwise return None
-            (Term::Variable(v), Term::Type(t)) => {
-                match variable_map.insert(v.to_string(), t.to_string()) {
-                    None => {}
-                    Some(old_t) => {
-                        if old_t != *t {
-                            return None;
-                        }
+            (Term::Variable(v), Term::Type(t))
+            | (Term::Variable(v), Term::Variable(t))
+            | (Term::Variable(v), Term::Literal(t)) => {
+                if let Some(old_t) = variable_map.insert(v.to_string(), t.to_string()) {
+                    if old_t != *t {
+                        return Err(Failure::InconsistentBinding {
+                            variable: v.to_string(),
+                            first: old_t,
+                            second: t.to_string(),
+                        });
                     }
                 }
             }
             // Check if concrete types match
-            (Term::Type(t1), Term::Type(t2)) => {
+            (Term::Type(t1), Term::Type(t2)) | (Term::Literal(t1), Term::Literal(t2)) => {
                 if t1 != t2 {
-                    return None;
+                    return Err(mismatch(t1, t2));
                 }
             }
-            // At this stage, only concrete target types are supported
-            (_, Term::Variable(_)) => {
-                panic!("Expected target alias to be concrete type")
+            // A concrete type can't stand in for a type variable of the target
+            (Term::Type(t), Term::Variable(v)) => return Err(mismatch(t, v)),
+            // Literals only match identical literals
+            (Term::Literal(t1), Term::Type(t2) | Term::Variable(t2))
+            | (Term::Type(t1), Term::Literal(t2)) => return Err(mismatch(t1, t2)),
+        }
+    }
+
+    Ok(variable_map)
+}
+
+pub(crate) fn substitute(tree: &TypeTree, variable_map: &HashMap<String, String>) -> String {
+    // Renders the source text of a tree with type variables replaced using `variable_map`
+    let mut out = String::new();
+    let mut last = tree.start_byte;
+
+    for v in find_kind(tree, "type_variable") {
+        if let Some(t) = variable_map.get(&v.text) {
+            out.push_str(&tree.text[last - tree.start_byte..v.start_byte - tree.start_byte]);
+            out.push_str(t);
+            last = v.end_byte;
+        }
+    }
+    out.push_str(&tree.text[last - tree.start_byte..]);
+
+    out
+}
+
+fn parse_head(head: &str) -> (String, Vec<Param>, bool) {
+    // Splits the head of an alias into its name and parameters
+    // Parameters may have kind annotations such as `(a :: Type)`. Infix heads such as `f ~> g`
+    // have the name of the alias second
+    let mut words: Vec<String> = Vec::new();
+    let mut depth = 0;
+    for c in head.chars() {
+        match c {
+            c if c.is_whitespace() && depth == 0 => {
+                words.push(String::new());
+                continue;
             }
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        match words.last_mut() {
+            Some(word) => word.push(c),
+            None => words.push(c.to_string()),
         }
     }
+    words.retain(|w| !w.is_empty());
+
+    let infix = words.len() > 2 && is_operator(&words[1]);
+    let name = if infix {
+        words.remove(1)
+    } else {
+        words.remove(0)
+    };
+
+    let params = words
+        .iter()
+        .map(
+            |w| match w.strip_prefix('(').and_then(|w| w.strip_suffix(')')) {
+                Some(annotated) => match annotated.split_once("::") {
+                    Some((v, k)) => Param {
+                        name: v.trim().to_string(),
+                        kind: Some(k.trim().to_string()),
+                    },
+                    None => Param {
+                        name: annotated.trim().to_string(),
+                        kind: None,
+                    },
+                },
+                None => Param {
+                    name: w.to_string(),
+                    kind: None,
+                },
+            },
+        )
+        .collect();
 
-    Some(variable_map)
+    (name, params, infix)
+}
+
+fn apply_head(alias: &Alias, variable_map: &HashMap<String, String>) -> String {
+    // Substitute concrete types in for the type parameters of an alias
+    let params: Vec<&str> = alias
+        .params
+        .iter()
+        .map(|p| variable_map.get(&p.name).unwrap_or(&p.name).as_str())
+        .collect();
+
+    if alias.infix {
+        format!("{} {} {}", params[0], alias.name, params[1..].join(" "))
+            .trim_end()
+            .to_string()
+    } else {
+        [vec![alias.name.as_str()], params].concat().join(" ")
+    }
+}
+
+fn is_operator(word: &str) -> bool {
+    // Checks if a word is a symbolic or ticked operator
+    word.starts_with('`') || !word.starts_with(|c: char| c.is_alphanumeric() || "_('".contains(c))
+}
+
+fn add_context(quantified: &[String], constraints: &[String], replaced: &str) -> String {
+    // Puts the leftover quantifiers and constraints of a target back in front of a replacement
+    let mut out = String::new();
+
+    if !quantified.is_empty() {
+        out.push_str(&format!("forall {}. ", quantified.join(" ")));
+    }
+
+    match constraints.len() {
+        0 => {}
+        1 => out.push_str(&format!("{} => ", constraints[0])),
+        _ => out.push_str(&format!("({}) => ", constraints.join(", "))),
+    }
+
+    out.push_str(replaced);
+    out
+}
+
+fn mentions(text: &str, name: &str) -> bool {
+    // Checks if `name` appears as an identifier in `text`
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '\''))
+        .any(|w| w == name)
+}
+
+pub(crate) fn normalise_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn node_range(node: &TSNode) -> Range {
+    // Location of a Treesitter node
+    Range {
+        start: Position {
+            row: node.start_position().row,
+            col: node.start_position().column,
+        },
+        end: Position {
+            row: node.end_position().row,
+            col: node.end_position().column,
+        },
+    }
 }
@@ -0,0 +1,244 @@
+// Contains the logic for rewriting a source file with replacements chosen from its matches, and
+// for showing the rewrite as a unified diff
+
+use crate::alias::contains_comment;
+use crate::literate::unliterate;
+use crate::types::{Match, Position, RequestApply, ResponseApply, SkippedMatch};
+
+// Lines of unchanged source shown around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+pub fn apply_matches(request: RequestApply) -> ResponseApply {
+    // Public API function to replace the occurrence of each match with its replacement
+    // Details on RequestApply and ResponseApply can be found in types.rs
+    // Only the text of each occurrence is changed so comments and layout elsewhere are kept.
+    // Matches are applied in the order given and a match overlapping one which has already been
+    // applied is skipped
+    // Occurrences in a literate source are found in its code, where bird tracks are blanked, so
+    // they are checked against the code but replaced in the source. Rows and columns are the same
+    // in both, and an occurrence spanning bird track lines is replaced by a single line
+    let source = &request.source;
+    let code = if request.literate {
+        unliterate(source)
+    } else {
+        source.clone()
+    };
+    let source_lines = line_starts(source);
+    let code_lines = line_starts(&code);
+    let offset = |text: &str, line_starts: &[usize], p: &Position| {
+        let start = *line_starts.get(p.row)?;
+        let end = line_starts.get(p.row + 1).map_or(text.len(), |e| e - 1);
+        Some(start + p.col).filter(|o| *o <= end && text.is_char_boundary(*o))
+    };
+
+    let mut edits: Vec<(usize, usize, &Match)> = Vec::new();
+    let mut skipped = Vec::new();
+    for m in request.matches.iter() {
+        let mut skip = |reason: &str| {
+            skipped.push(SkippedMatch {
+                matched: m.clone(),
+                reason: reason.to_string(),
+            })
+        };
+
+        let occurrence = match &m.occurrence {
+            Some(o) => o,
+            None => {
+                skip("The match has no occurrence in the source to replace");
+                continue;
+            }
+        };
+        let location = &occurrence.location;
+        let (start, end, code_start, code_end) = match (
+            offset(source, &source_lines, &location.start),
+            offset(source, &source_lines, &location.end),
+            offset(&code, &code_lines, &location.start),
+            offset(&code, &code_lines, &location.end),
+        ) {
+            (Some(start), Some(end), Some(code_start), Some(code_end))
+                if start <= end && code_start <= code_end =>
+            {
+                (start, end, code_start, code_end)
+            }
+            _ => {
+                skip("The occurrence is outside of the source");
+                continue;
+            }
+        };
+
+        let text = &code[code_start..code_end];
+        if text != occurrence.text {
+            skip("The source at the occurrence no longer contains the replaced type");
+        } else if contains_comment(text) {
+            skip("Replacing the occurrence would remove a comment");
+        } else if edits
+            .iter()
+            .any(|(s, e, a)| (*s, *e) == (start, end) && a.replaced_type == m.replaced_type)
+        {
+            // The same replacement selected twice is only applied once
+        } else if edits.iter().any(|(s, e, _)| start < *e && *s < end) {
+            skip("The occurrence overlaps a replacement which has already been applied");
+        } else {
+            edits.push((start, end, m));
+        }
+    }
+
+    // Edits are made from the end of the source so earlier offsets stay correct
+    edits.sort_by_key(|(start, _, _)| *start);
+    let mut rewritten = source.clone();
+    for (start, end, m) in edits.iter().rev() {
+        rewritten.replace_range(start..end, &m.replaced_type);
+    }
+
+    ResponseApply {
+        source: rewritten,
+        applied: edits.into_iter().map(|(_, _, m)| m.clone()).collect(),
+        skipped,
+        echo_request: request,
+    }
+}
+
+fn line_starts(text: &str) -> Vec<usize> {
+    // The byte offset at which each line of some text starts
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+// A step from the lines of the original file to those of the rewritten file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    Keep,
+    Delete,
+    Insert,
+}
+
+pub fn unified_diff(original: &str, rewritten: &str, path: &str) -> String {
+    // Formats the changes from `original` to `rewritten` as a unified diff
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = rewritten.lines().collect();
+    let steps = diff_lines(&old, &new);
+
+    // Position in each file before every step
+    let mut positions = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for step in steps.iter() {
+        positions.push((i, j));
+        match step {
+            Step::Keep => (i, j) = (i + 1, j + 1),
+            Step::Delete => i += 1,
+            Step::Insert => j += 1,
+        }
+    }
+
+    // Changes closer together than twice the context share a hunk
+    let changes: Vec<usize> = (0..steps.len())
+        .filter(|s| steps[*s] != Step::Keep)
+        .collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for change in changes {
+        let start = change.saturating_sub(DIFF_CONTEXT);
+        let end = (change + DIFF_CONTEXT + 1).min(steps.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for (start, end) in hunks {
+        let steps = &steps[start..end];
+        let (old_start, new_start) = positions[start];
+        let old_len = steps.iter().filter(|s| **s != Step::Insert).count();
+        let new_len = steps.iter().filter(|s| **s != Step::Delete).count();
+
+        // Empty ranges start at the line before them
+        let header = |start: usize, len: usize| {
+            let start = if len == 0 { start } else { start + 1 };
+            format!("{},{}", start, len)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            header(old_start, old_len),
+            header(new_start, new_len)
+        ));
+
+        let (mut i, mut j) = (old_start, new_start);
+        for step in steps {
+            match step {
+                Step::Keep => {
+                    out.push_str(&format!(" {}\n", old[i]));
+                    (i, j) = (i + 1, j + 1);
+                }
+                Step::Delete => {
+                    out.push_str(&format!("-{}\n", old[i]));
+                    i += 1;
+                }
+                Step::Insert => {
+                    out.push_str(&format!("+{}\n", new[j]));
+                    j += 1;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Step> {
+    // Finds the shortest list of steps between two lists of lines with Myers' algorithm
+    // `furthest[k]` is the furthest line of `old` reached on diagonal `k`, offset by `max`
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (k + max) as usize;
+    let mut furthest = vec![0isize; 2 * max as usize + 2];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(furthest.clone());
+        for k in (-d..=d).step_by(2) {
+            let down = k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]);
+            let mut x = if down {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                (x, y) = (x + 1, y + 1);
+            }
+            furthest[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back from the end of both files through the furthest points of each round
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, furthest) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let down = k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]);
+        let previous_k = if down { k + 1 } else { k - 1 };
+        let previous_x = furthest[index(previous_k)];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            steps.push(Step::Keep);
+            (x, y) = (x - 1, y - 1);
+        }
+        if d > 0 {
+            steps.push(if down { Step::Insert } else { Step::Delete });
+        }
+        (x, y) = (previous_x, previous_y);
+    }
+
+    steps.reverse();
+    steps
+}
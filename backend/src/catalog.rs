@@ -0,0 +1,145 @@
+// Contains the logic for comparing the aliases declared across a set of files with each other,
+// finding aliases which are equivalent and aliases which are instances of more general ones
+
+use std::collections::HashMap;
+
+use crate::alias::{
+    get_shape, get_terms, normalise_whitespace, substitute, target_from_tree, try_match_alias,
+};
+use crate::kind::get_kinds;
+use crate::project::{read_module, ModuleKind};
+use crate::types::{Alias, CatalogEntry, RequestCatalog, ResponseCatalog, Specialisation, Term};
+
+pub fn catalog_aliases(request: RequestCatalog) -> ResponseCatalog {
+    // Public API function to compare every alias declared in a set of files
+    // Details on RequestCatalog and ResponseCatalog can be found in types.rs
+    // Boot files and signatures repeat the aliases of the modules implementing them, so only
+    // source modules are compared
+    let modules: Vec<_> = request
+        .files
+        .iter()
+        .filter_map(|file| read_module(file, &request.default_extensions, &request.defines))
+        .filter(|m| m.kind == ModuleKind::Source)
+        .collect();
+
+    // Kinds declared in any module are used to check instances
+    let mut kinds = HashMap::new();
+    let mut aliases: Vec<(Alias, CatalogEntry)> = Vec::new();
+    for module in modules.iter() {
+        kinds.extend(get_kinds(&module.source));
+        for alias in module.aliases.iter() {
+            let entry = CatalogEntry {
+                alias: alias.name.clone(),
+                declaration: alias.declaration.clone(),
+                location: alias.location.clone(),
+                module: module.name.clone(),
+                path: module.file.path.clone(),
+            };
+            aliases.push((alias.clone(), entry));
+        }
+    }
+
+    // Aliases with the same canonical form are equivalent
+    let forms: Vec<String> = aliases.iter().map(|(a, _)| canonical_form(a)).collect();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, form) in forms.iter().enumerate() {
+        match groups.iter_mut().find(|g| forms[g[0]] == *form) {
+            Some(group) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+    let equivalent = groups
+        .iter()
+        .filter(|g| g.len() > 1)
+        .map(|g| g.iter().map(|i| aliases[*i].1.clone()).collect())
+        .collect();
+
+    // An alias is an instance of another if the other replaces its right hand side. Aliases
+    // which are only a lone type variable would make every alias an instance of them
+    let mut specialisations = Vec::new();
+    for (i, (specific, specific_entry)) in aliases.iter().enumerate() {
+        let target = target_from_tree(specific.rhs.clone());
+        for (j, (general, general_entry)) in aliases.iter().enumerate() {
+            if forms[i] == forms[j] || matches!(get_terms(&general.rhs)[..], [Term::Variable(_)]) {
+                continue;
+            }
+            let m = match try_match_alias(&target, general, &kinds) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if renames_variables(general, &m.variable_map) {
+                continue;
+            }
+            specialisations.push(Specialisation {
+                specific: specific_entry.clone(),
+                general: general_entry.clone(),
+                instance: m.replaced_type,
+            });
+        }
+    }
+
+    ResponseCatalog {
+        echo_request: request,
+        equivalent,
+        specialisations,
+    }
+}
+
+fn canonical_form(alias: &Alias) -> String {
+    // Writes an alias with its parameters renamed by position and its other type variables
+    // renamed by where they are first used. Aliases which are equal up to renaming their
+    // variables have the same canonical form
+    let mut renaming: HashMap<String, String> = alias
+        .params
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.name.clone(), format!("p{}", i)))
+        .collect();
+
+    let terms: Vec<String> = get_terms(&alias.rhs)
+        .into_iter()
+        .map(|term| match term {
+            Term::Variable(v) => {
+                let next = format!("v{}", renaming.len());
+                renaming.entry(v).or_insert(next).clone()
+            }
+            Term::Type(t) | Term::Literal(t) => t,
+        })
+        .collect();
+
+    // The order of constraints doesn't matter
+    let mut constraints: Vec<String> = alias
+        .constraints
+        .iter()
+        .map(|c| normalise_whitespace(&substitute(c, &renaming)))
+        .collect();
+    constraints.sort();
+
+    format!(
+        "{} {} {} {} {}",
+        alias.params.len(),
+        alias.quantified.len(),
+        constraints.join(", "),
+        get_shape(&alias.rhs),
+        terms.join(" ")
+    )
+}
+
+fn renames_variables(general: &Alias, variable_map: &HashMap<String, String>) -> bool {
+    // Whether the parameters of `general` are only bound to distinct type variables, in which
+    // case the other alias is no more specific than it
+    let mut bound: Vec<&String> = Vec::new();
+    general
+        .params
+        .iter()
+        .all(|p| match variable_map.get(&p.name) {
+            Some(t)
+                if t.starts_with(|c: char| c.is_lowercase() || c == '_') && !bound.contains(&t) =>
+            {
+                bound.push(t);
+                true
+            }
+            Some(_) => false,
+            None => true,
+        })
+}
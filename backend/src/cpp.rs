@@ -0,0 +1,403 @@
+// Contains the logic for handling C preprocessor directives in modules which use the CPP
+// extension. Directives and inactive branches are blanked so the remaining code keeps the row and
+// column it has in the original file
+
+use std::collections::HashMap;
+
+use crate::legality::get_extensions;
+
+// Directives which the preprocessor removes from the source
+const DIRECTIVES: [&str; 12] = [
+    "if", "ifdef", "ifndef", "elif", "else", "endif", "define", "undef", "include", "error",
+    "warning", "line",
+];
+
+// Without defines, versions of the source are analysed until every branch of every conditional
+// has been, up to this many versions
+const MAX_VARIANTS: usize = 8;
+
+// How the branch of a conditional is chosen. Without defines, each conditional takes the branch
+// chosen for the line it starts on, or its first branch
+enum Mode {
+    Defines(HashMap<String, String>),
+    Branch(HashMap<usize, usize>),
+}
+
+// A conditional which is open at the current line
+// `chosen` is the branch to take when branches are chosen rather than evaluated and `reached`
+// is its position in the list of conditionals outside of any inactive branch
+struct Frame {
+    enclosing: bool,
+    branch: usize,
+    taken: bool,
+    active: bool,
+    has_else: bool,
+    chosen: Option<usize>,
+    reached: Option<usize>,
+}
+
+// A conditional outside of any inactive branch, with the line it starts on, its number of
+// branches and the branch taken
+struct Reached {
+    line: usize,
+    branches: usize,
+    branch: usize,
+}
+
+pub fn preprocess(
+    source: &str,
+    default_extensions: &[String],
+    defines: &Option<Vec<String>>,
+) -> Vec<String> {
+    // Returns the versions of `source` which should be analysed
+    // Sources which don't enable CPP are analysed as they are. Given defines, the conditionals
+    // are evaluated as the preprocessor would. Otherwise every branch is in some version
+    let uses_cpp = get_extensions(source, default_extensions)
+        .iter()
+        .any(|e| e == "CPP");
+
+    match defines {
+        _ if !uses_cpp => vec![source.to_string()],
+        Some(defines) => vec![process(source, &Mode::Defines(parse_defines(defines))).0],
+        None => every_branch(source),
+    }
+}
+
+fn every_branch(source: &str) -> Vec<String> {
+    // Each version takes, in every conditional it reaches, a branch which no earlier version
+    // took. Branches nested inside another are reached once the enclosing branch is taken
+    let mut chosen: HashMap<usize, usize> = HashMap::new();
+    let mut covered: Vec<(usize, usize)> = Vec::new();
+    let mut variants = Vec::new();
+
+    loop {
+        let (variant, reached) = process(source, &Mode::Branch(chosen.clone()));
+        variants.push(variant);
+        for r in reached.iter() {
+            if !covered.contains(&(r.line, r.branch)) {
+                covered.push((r.line, r.branch));
+            }
+        }
+
+        let mut changed = false;
+        for r in reached.iter() {
+            if let Some(b) = (0..r.branches).find(|b| !covered.contains(&(r.line, *b))) {
+                chosen.insert(r.line, b);
+                changed = true;
+            }
+        }
+        if !changed || variants.len() == MAX_VARIANTS {
+            return variants;
+        }
+    }
+}
+
+fn parse_defines(defines: &[String]) -> HashMap<String, String> {
+    // Defines are given as `NAME` or `NAME=VALUE` like the `-D` option of the preprocessor
+    defines
+        .iter()
+        .map(|d| match d.split_once('=') {
+            Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+            None => (d.trim().to_string(), "1".to_string()),
+        })
+        .collect()
+}
+
+fn directive(line: &str) -> Option<(&str, &str)> {
+    // Splits a preprocessor directive such as `#if MIN_VERSION_base(4,12,0)` into its name and
+    // the rest of the line
+    let line = line.strip_prefix('#')?.trim_start();
+    let end = line
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(line.len());
+    let (name, rest) = line.split_at(end);
+
+    if DIRECTIVES.contains(&name) {
+        Some((name, rest.trim()))
+    } else {
+        None
+    }
+}
+
+fn process(source: &str, mode: &Mode) -> (String, Vec<Reached>) {
+    // Blanks the directives of `source` and every line in an inactive branch
+    // Also lists the conditionals reached, counting the empty `#else` of a conditional without
+    // one as a branch
+    let mut defines = match mode {
+        Mode::Defines(defines) => defines.clone(),
+        Mode::Branch(_) => HashMap::new(),
+    };
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut reached: Vec<Reached> = Vec::new();
+    let mut out: Vec<String> = Vec::new();
+    let mut lines = source.split('\n');
+
+    while let Some(line) = lines.next() {
+        let start = out.len();
+        let (name, rest) = match directive(line) {
+            Some(d) => d,
+            None => {
+                let active = stack.last().is_none_or(|f| f.enclosing && f.active);
+                out.push(if active {
+                    line.to_string()
+                } else {
+                    String::new()
+                });
+                continue;
+            }
+        };
+
+        // Directives continue onto the next line after a backslash
+        let mut rest = rest.trim_end_matches('\r').to_string();
+        out.push(String::new());
+        while rest.ends_with('\\') {
+            rest.pop();
+            match lines.next() {
+                Some(next) => {
+                    rest.push_str(next.trim_end_matches('\r'));
+                    out.push(String::new());
+                }
+                None => break,
+            }
+        }
+
+        let enclosing = stack.last().is_none_or(|f| f.enclosing && f.active);
+        match name {
+            "if" | "ifdef" | "ifndef" => {
+                let condition = match name {
+                    "ifdef" => format!("defined({})", rest.trim()),
+                    "ifndef" => format!("!defined({})", rest.trim()),
+                    _ => rest,
+                };
+                let chosen = match mode {
+                    Mode::Defines(_) => None,
+                    Mode::Branch(lines) => Some(lines.get(&start).copied().unwrap_or(0)),
+                };
+                let active = choose(chosen, Some(&condition), 0, &defines);
+                if enclosing {
+                    reached.push(Reached {
+                        line: start,
+                        branches: 1,
+                        branch: chosen.unwrap_or(0),
+                    });
+                }
+                stack.push(Frame {
+                    enclosing,
+                    branch: 0,
+                    taken: active,
+                    active,
+                    has_else: false,
+                    chosen,
+                    reached: enclosing.then_some(reached.len() - 1),
+                });
+            }
+            "elif" | "else" => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.branch += 1;
+                    frame.has_else |= name == "else";
+                    let condition = (name == "elif").then_some(rest.as_str());
+                    frame.active =
+                        !frame.taken && choose(frame.chosen, condition, frame.branch, &defines);
+                    frame.taken |= frame.active;
+                }
+            }
+            "endif" => {
+                if let Some(frame) = stack.pop() {
+                    if let Some(r) = frame.reached {
+                        reached[r].branches = frame.branch + 1 + usize::from(!frame.has_else);
+                    }
+                }
+            }
+            "define" if enclosing => {
+                let (macro_name, value) =
+                    rest.split_once(char::is_whitespace).unwrap_or((&rest, ""));
+                let value = if value.trim().is_empty() {
+                    "1"
+                } else {
+                    value.trim()
+                };
+                defines.insert(macro_name.to_string(), value.to_string());
+            }
+            "undef" if enclosing => {
+                defines.remove(rest.trim());
+            }
+            _ => {}
+        }
+    }
+
+    (out.join("\n"), reached)
+}
+
+fn choose(
+    chosen: Option<usize>,
+    condition: Option<&str>,
+    branch: usize,
+    defines: &HashMap<String, String>,
+) -> bool {
+    // Decides if a branch is active, either the chosen branch or the first whose condition
+    // holds. An `#else` branch has no condition
+    match chosen {
+        Some(chosen) => branch == chosen,
+        None => condition.is_none_or(|c| evaluate(c, defines) != 0),
+    }
+}
+
+fn evaluate(condition: &str, defines: &HashMap<String, String>) -> i64 {
+    // Evaluates the condition of an `#if` or `#elif`
+    // As in C, identifiers which aren't defined are 0
+    let tokens = tokenise(condition);
+    let mut parser = Condition {
+        tokens: &tokens,
+        position: 0,
+        defines,
+    };
+    parser.or()
+}
+
+fn tokenise(condition: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = condition.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            if ["&&", "||", "==", "!=", "<=", ">="].contains(&pair.as_str()) {
+                tokens.push(pair);
+                i += 2;
+            } else {
+                tokens.push(c.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+// Recursive descent parser for the conditions of `#if` directives
+struct Condition<'a> {
+    tokens: &'a [String],
+    position: usize,
+    defines: &'a HashMap<String, String>,
+}
+
+impl Condition<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        self.position += 1;
+        self.tokens.get(self.position - 1).map(|t| t.as_str())
+    }
+
+    fn or(&mut self) -> i64 {
+        let mut value = self.and();
+        while self.peek() == Some("||") {
+            self.next();
+            let rhs = self.and();
+            value = i64::from(value != 0 || rhs != 0);
+        }
+        value
+    }
+
+    fn and(&mut self) -> i64 {
+        let mut value = self.comparison();
+        while self.peek() == Some("&&") {
+            self.next();
+            let rhs = self.comparison();
+            value = i64::from(value != 0 && rhs != 0);
+        }
+        value
+    }
+
+    fn comparison(&mut self) -> i64 {
+        let lhs = self.unary();
+        let op = match self.peek() {
+            Some(op @ ("==" | "!=" | "<" | ">" | "<=" | ">=")) => op.to_string(),
+            _ => return lhs,
+        };
+        self.next();
+        let rhs = self.unary();
+
+        i64::from(match op.as_str() {
+            "==" => lhs == rhs,
+            "!=" => lhs != rhs,
+            "<" => lhs < rhs,
+            ">" => lhs > rhs,
+            "<=" => lhs <= rhs,
+            _ => lhs >= rhs,
+        })
+    }
+
+    fn unary(&mut self) -> i64 {
+        match self.peek() {
+            Some("!") => {
+                self.next();
+                i64::from(self.unary() == 0)
+            }
+            Some("-") => {
+                self.next();
+                -self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> i64 {
+        let token = match self.next() {
+            Some(t) => t.to_string(),
+            None => return 0,
+        };
+
+        if token == "(" {
+            let value = self.or();
+            self.next();
+            return value;
+        }
+
+        if token == "defined" {
+            let parens = self.peek() == Some("(");
+            if parens {
+                self.next();
+            }
+            let name = self.next().unwrap_or_default().to_string();
+            if parens {
+                self.next();
+            }
+            return i64::from(self.defines.contains_key(&name));
+        }
+
+        if let Ok(number) = token.trim_end_matches(['L', 'U', 'l', 'u']).parse() {
+            return number;
+        }
+
+        // Function-like macros such as `MIN_VERSION_base(4,12,0)` are only known if defined
+        if self.peek() == Some("(") {
+            let mut depth = 0;
+            while let Some(t) = self.next() {
+                match t {
+                    "(" => depth += 1,
+                    ")" if depth == 1 => break,
+                    ")" => depth -= 1,
+                    _ => {}
+                }
+            }
+        }
+
+        match self.defines.get(&token) {
+            Some(value) => value.parse().unwrap_or(1),
+            None => 0,
+        }
+    }
+}
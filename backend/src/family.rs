@@ -0,0 +1,335 @@
+// Contains the logic for indexing the type families declared in a source file and for
+// evaluating type family applications which appear in the right hand side of type aliases
+
+use regex::Regex;
+use std::collections::HashMap;
+
+// Treesitter
+use tree_sitter::Node as TSNode;
+
+use crate::alias::{find_nodes, get_terms, is_associated, normalise_whitespace, parse_type};
+use crate::types::{Equation, Fixity, Term, TypeFamily, TypeTree};
+
+// Families which never reach a normal form are only evaluated this many times
+const MAX_REDUCTIONS: usize = 50;
+
+// Result of matching an equation against the arguments of a type family application
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Match,
+    Apart,
+    Blocked,
+}
+
+pub fn get_families(
+    root: &TSNode,
+    source: &[u8],
+    fixities: &HashMap<String, Fixity>,
+) -> Vec<TypeFamily> {
+    // Indexes closed type families, `type instance` equations and associated types
+
+    let mut families: Vec<TypeFamily> = Vec::new();
+    let re_family = Regex::new(r"^type\s+family\s+([^\s(]+)").unwrap();
+    let re_instance = Regex::new(r"^type\s+(instance\s+)?").unwrap();
+
+    // Declarations of families. Closed families list their equations after `where`
+    for node in find_nodes(root, &["type_family"]) {
+        let text = node.utf8_text(source).unwrap();
+        let name = match re_family.captures(text) {
+            Some(c) => c[1].to_string(),
+            None => continue,
+        };
+        let closed = text.contains(" where");
+
+        let family = get_family(&mut families, &name);
+        family.closed = closed;
+
+        if let Some((_, equations)) = text.split_once(" where") {
+            for line in equations.split(['\n', ';']) {
+                if let Some((_, equation)) = parse_equation(line, fixities) {
+                    family.equations.push(equation);
+                }
+            }
+        }
+    }
+
+    // Open family instances, associated instances and associated defaults
+    for node in find_nodes(root, &["type_instance", "type_alias"]) {
+        let text = node.utf8_text(source).unwrap();
+        let is_instance = node.kind() == "type_instance" || text.starts_with("type instance");
+        let associated = is_associated(&node);
+        if !is_instance && !associated {
+            continue;
+        }
+
+        let in_class = associated && !is_instance && in_class_declaration(&node);
+        let equation_text = re_instance.replace(text, "");
+        if let Some((name, equation)) = parse_equation(&equation_text, fixities) {
+            let family = get_family(&mut families, &name);
+            if in_class {
+                family.defaults.push(equation);
+            } else {
+                family.equations.push(equation);
+            }
+        }
+    }
+
+    families
+}
+
+fn get_family<'a>(families: &'a mut Vec<TypeFamily>, name: &str) -> &'a mut TypeFamily {
+    // Finds a family by name, adding an open family if it hasn't been seen yet
+    let i = match families.iter().position(|f| f.name == name) {
+        Some(i) => i,
+        None => {
+            families.push(TypeFamily {
+                name: name.to_string(),
+                closed: false,
+                equations: Vec::new(),
+                defaults: Vec::new(),
+            });
+            families.len() - 1
+        }
+    };
+    &mut families[i]
+}
+
+fn in_class_declaration(node: &TSNode) -> bool {
+    // Checks if a declaration is inside a class rather than an instance
+    let mut parent = node.parent();
+    while let Some(p) = parent {
+        if p.kind().starts_with("class") {
+            return true;
+        }
+        if p.kind().starts_with("instance") {
+            return false;
+        }
+        parent = p.parent();
+    }
+    false
+}
+
+fn parse_equation(line: &str, fixities: &HashMap<String, Fixity>) -> Option<(String, Equation)> {
+    // Splits an equation such as `Elem [a] = a` into the family name, its argument patterns and
+    // its right hand side
+    let re_equation = Regex::new(r"^\s*(.+?)\s*=([^>].*)$").unwrap();
+    let captures = re_equation.captures(line)?;
+
+    let lhs = parse_type(&captures[1], fixities)?;
+    let rhs = parse_type(captures[2].trim(), fixities)?;
+
+    let (head, args) = match lhs.kind.as_str() {
+        "type_apply" => (lhs.children[0].clone(), lhs.children[1..].to_vec()),
+        "type_name" => (lhs, Vec::new()),
+        _ => return None,
+    };
+
+    Some((head.text, Equation { args, rhs }))
+}
+
+pub fn reduce_families(
+    tree: TypeTree,
+    families: &[TypeFamily],
+    fixities: &HashMap<String, Fixity>,
+) -> TypeTree {
+    // Evaluates type family applications in a tree until none can be reduced
+    // Each reduction is rendered as text and parsed again so the tree stays consistent
+    let mut tree = tree;
+
+    for _ in 0..MAX_REDUCTIONS {
+        match reduce_once(&tree, families).and_then(|text| parse_type(&text, fixities)) {
+            Some(reduced) => tree = reduced,
+            None => break,
+        }
+    }
+
+    tree
+}
+
+fn reduce_once(tree: &TypeTree, families: &[TypeFamily]) -> Option<String> {
+    // Returns the text of `tree` with its first reducible family application evaluated
+    if let Some(reduced) = reduce_application(tree, families) {
+        return Some(reduced);
+    }
+
+    for child in tree.children.iter() {
+        if let Some(reduced) = reduce_once(child, families) {
+            // Parentheses around an application aren't needed once it reduces to an atom
+            if tree.kind == "type_parens" && is_atomic(&reduced) {
+                return Some(reduced);
+            }
+
+            let start = child.start_byte - tree.start_byte;
+            let end = child.end_byte - tree.start_byte;
+            return Some(format!(
+                "{}{}{}",
+                &tree.text[..start],
+                reduced,
+                &tree.text[end..]
+            ));
+        }
+    }
+
+    None
+}
+
+fn reduce_application(tree: &TypeTree, families: &[TypeFamily]) -> Option<String> {
+    // Evaluates `tree` if it is a saturated application of a known family
+    let (head, args) = match tree.kind.as_str() {
+        "type_apply" => (tree.children.first()?, &tree.children[1..]),
+        "type_name" => (tree, &tree.children[..0]),
+        _ => return None,
+    };
+
+    let name = match &get_terms(head)[..] {
+        [Term::Type(name)] => name.clone(),
+        _ => return None,
+    };
+    let family = families.iter().find(|f| f.name == name)?;
+
+    for equation in family.equations.iter() {
+        if equation.args.len() != args.len() {
+            continue;
+        }
+
+        let mut bindings = HashMap::new();
+        let mut outcome = Outcome::Match;
+        for (pattern, arg) in equation.args.iter().zip(args.iter()) {
+            outcome = match_pattern(pattern, arg, &mut bindings);
+            if outcome != Outcome::Match {
+                break;
+            }
+        }
+
+        match outcome {
+            Outcome::Match => return Some(instantiate(&equation.rhs, &bindings)),
+            Outcome::Apart => continue,
+            // Equations of a closed family can't be skipped while an earlier one might still
+            // match once a type variable is known
+            Outcome::Blocked if family.closed => return None,
+            Outcome::Blocked => continue,
+        }
+    }
+
+    None
+}
+
+fn match_pattern(
+    pattern: &TypeTree,
+    actual: &TypeTree,
+    bindings: &mut HashMap<String, TypeTree>,
+) -> Outcome {
+    // Matches an argument pattern of an equation against an actual argument
+    // Unlike alias parameters, pattern variables can be bound to any type
+    let pattern = strip_parens(pattern);
+    let actual = strip_parens(actual);
+
+    if is_variable(pattern) {
+        return match bindings.get(&pattern.text) {
+            None => {
+                bindings.insert(pattern.text.clone(), actual.clone());
+                Outcome::Match
+            }
+            Some(bound)
+                if normalise_whitespace(&bound.text) == normalise_whitespace(&actual.text) =>
+            {
+                Outcome::Match
+            }
+            Some(bound) if has_variables(bound) || has_variables(actual) => Outcome::Blocked,
+            Some(_) => Outcome::Apart,
+        };
+    }
+
+    if is_variable(actual) {
+        return Outcome::Blocked;
+    }
+
+    if pattern.kind != actual.kind || pattern.children.len() != actual.children.len() {
+        return Outcome::Apart;
+    }
+
+    if pattern.kind == "type_name" || pattern.children.is_empty() {
+        return if get_terms(pattern) == get_terms(actual) {
+            Outcome::Match
+        } else {
+            Outcome::Apart
+        };
+    }
+
+    let mut outcome = Outcome::Match;
+    for (p, a) in pattern.children.iter().zip(actual.children.iter()) {
+        match match_pattern(p, a, bindings) {
+            Outcome::Apart => return Outcome::Apart,
+            Outcome::Blocked => outcome = Outcome::Blocked,
+            Outcome::Match => {}
+        }
+    }
+    outcome
+}
+
+fn instantiate(tree: &TypeTree, bindings: &HashMap<String, TypeTree>) -> String {
+    // Renders `tree` with bound variables replaced, adding parentheses where a compound type is
+    // applied to something or used as an operand
+    if is_variable(tree) {
+        return bindings
+            .get(&tree.text)
+            .map_or(tree.text.clone(), |b| b.text.clone());
+    }
+
+    let mut out = String::new();
+    let mut last = tree.start_byte;
+
+    for (i, child) in tree.children.iter().enumerate() {
+        out.push_str(&tree.text[last - tree.start_byte..child.start_byte - tree.start_byte]);
+
+        let rendered = instantiate(child, bindings);
+        let argument = ["type_apply", "type_infix"].contains(&tree.kind.as_str());
+        let domain = tree.kind == "fun"
+            && i == 0
+            && bindings
+                .get(&child.text)
+                .is_some_and(|b| ["fun", "forall", "context"].contains(&b.kind.as_str()));
+
+        if is_variable(child) && !is_atomic(&rendered) && (argument || domain) {
+            out.push_str(&format!("({})", rendered));
+        } else {
+            out.push_str(&rendered);
+        }
+        last = child.end_byte;
+    }
+    out.push_str(&tree.text[last - tree.start_byte..]);
+
+    out
+}
+
+fn strip_parens(tree: &TypeTree) -> &TypeTree {
+    match &tree.children[..] {
+        [inner] if tree.kind == "type_parens" => strip_parens(inner),
+        _ => tree,
+    }
+}
+
+pub(crate) fn is_variable(tree: &TypeTree) -> bool {
+    matches!(&get_terms(tree)[..], [Term::Variable(_)])
+        && (tree.kind == "type_name" || tree.kind == "type_variable")
+}
+
+fn has_variables(tree: &TypeTree) -> bool {
+    get_terms(tree)
+        .iter()
+        .any(|t| matches!(t, Term::Variable(_)))
+}
+
+fn is_atomic(text: &str) -> bool {
+    // Checks if a type needs no parentheses when used as an argument
+    let mut depth = 0;
+    for c in text.trim().chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => return false,
+            _ => {}
+        }
+    }
+    true
+}
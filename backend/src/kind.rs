@@ -0,0 +1,232 @@
+// Contains the logic for reading the declared kinds of type alias parameters and for checking
+// that types substituted for those parameters have the declared kinds
+
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::types::Param;
+
+// Kinds of common types and classes from base, containers and mtl which are rarely declared in
+// the source
+const BUILTIN_KINDS: [(&str, &str); 35] = [
+    ("()", "Type"),
+    ("Bool", "Type"),
+    ("Char", "Type"),
+    ("Double", "Type"),
+    ("Float", "Type"),
+    ("Int", "Type"),
+    ("Integer", "Type"),
+    ("Ordering", "Type"),
+    ("String", "Type"),
+    ("Text", "Type"),
+    ("Word", "Type"),
+    ("IO", "Type -> Type"),
+    ("Maybe", "Type -> Type"),
+    ("Set", "Type -> Type"),
+    ("[]", "Type -> Type"),
+    ("Either", "Type -> Type -> Type"),
+    ("Map", "Type -> Type -> Type"),
+    ("Bounded", "Type -> Constraint"),
+    ("Enum", "Type -> Constraint"),
+    ("Eq", "Type -> Constraint"),
+    ("Fractional", "Type -> Constraint"),
+    ("Integral", "Type -> Constraint"),
+    ("Monoid", "Type -> Constraint"),
+    ("Num", "Type -> Constraint"),
+    ("Ord", "Type -> Constraint"),
+    ("Read", "Type -> Constraint"),
+    ("Semigroup", "Type -> Constraint"),
+    ("Show", "Type -> Constraint"),
+    ("Applicative", "(Type -> Type) -> Constraint"),
+    ("Foldable", "(Type -> Type) -> Constraint"),
+    ("Functor", "(Type -> Type) -> Constraint"),
+    ("Monad", "(Type -> Type) -> Constraint"),
+    ("MonadIO", "(Type -> Type) -> Constraint"),
+    ("Traversable", "(Type -> Type) -> Constraint"),
+    ("MonadReader", "Type -> (Type -> Type) -> Constraint"),
+];
+
+pub fn get_kind_signatures(source: &str) -> HashMap<String, Vec<String>> {
+    // Reads standalone kind signatures such as `type Const :: Type -> k -> Type`
+    // Returns the kinds of the parameters of each alias
+    read_kind_signatures(source)
+        .into_iter()
+        .map(|(name, kind)| {
+            let mut kinds = split_arrows(&kind);
+            kinds.pop();
+            (name, kinds)
+        })
+        .collect()
+}
+
+fn read_kind_signatures(source: &str) -> Vec<(String, String)> {
+    let re_signature = Regex::new(r"(?m)^type\s+([A-Z][\w']*|\([^)\s]+\))\s*::(.+)$").unwrap();
+
+    re_signature
+        .captures_iter(source)
+        .map(|c| (c[1].to_string(), c[2].to_string()))
+        .collect()
+}
+
+pub fn get_kinds(source: &str) -> HashMap<String, String> {
+    // Kinds of type constructors declared with `data` or `newtype` in the source, of classes
+    // and of aliases with standalone kind signatures, along with the kinds of common types
+    let re_data = Regex::new(r"(?m)^(data|newtype)\s+([A-Z][\w']*)((\s+[a-z][\w']*)*)").unwrap();
+    let re_class =
+        Regex::new(r"(?m)^class\s+(?:[^=\n]*=>\s*)?([A-Z][\w']*)((\s+[a-z][\w']*)*)").unwrap();
+
+    let mut kinds: HashMap<String, String> = BUILTIN_KINDS
+        .iter()
+        .map(|(t, k)| (t.to_string(), k.to_string()))
+        .collect();
+
+    for declaration in re_data.captures_iter(source) {
+        let arity = declaration[3].split_whitespace().count();
+        let kind = vec!["Type"; arity + 1].join(" -> ");
+        kinds.insert(declaration[2].to_string(), kind);
+    }
+    for declaration in re_class.captures_iter(source) {
+        let arity = declaration[2].split_whitespace().count();
+        let mut kind = vec!["Type"; arity];
+        kind.push("Constraint");
+        kinds.insert(declaration[1].to_string(), kind.join(" -> "));
+    }
+    for (name, kind) in read_kind_signatures(source) {
+        kinds.insert(name, normalise_kind(&kind));
+    }
+
+    kinds
+}
+
+pub fn is_constraint_kind(kind: &str) -> bool {
+    // Checks if a kind is that of a class or constraint synonym, such as `Type -> Constraint`
+    split_arrows(kind)
+        .last()
+        .is_some_and(|result| result == "Constraint")
+}
+
+pub fn check_kinds(
+    params: &[Param],
+    variable_map: &HashMap<String, String>,
+    kinds: &HashMap<String, String>,
+) -> bool {
+    // Checks that every type bound to a parameter with a declared kind has that kind
+    // Types with unknown kinds are assumed to be correct
+    kind_clash(params, variable_map, kinds).is_none()
+}
+
+pub fn kind_clash(
+    params: &[Param],
+    variable_map: &HashMap<String, String>,
+    kinds: &HashMap<String, String>,
+) -> Option<(String, String, String)> {
+    // Finds the first parameter bound to a type without its declared kind
+    // Returns the parameter, its declared kind and the kind of the type bound to it
+    let mut kind_variables = HashMap::new();
+
+    params.iter().find_map(|p| {
+        let declared = normalise_kind(p.kind.as_ref()?);
+        let inferred = variable_map
+            .get(&p.name)
+            .and_then(|t| infer_kind(t, kinds))?;
+        if kinds_compatible(&declared, &inferred, &mut kind_variables) {
+            None
+        } else {
+            Some((p.name.clone(), declared, inferred))
+        }
+    })
+}
+
+pub fn infer_kind(atom: &str, kinds: &HashMap<String, String>) -> Option<String> {
+    // Finds the kind of a type which has been bound to a parameter
+    let first = atom.chars().next()?;
+
+    if first == '"' {
+        Some("Symbol".to_string())
+    } else if first.is_ascii_digit() {
+        Some("Nat".to_string())
+    } else if first.is_lowercase() || first == '_' {
+        // Type variables could have any kind
+        None
+    } else {
+        // Qualified names use the kind of the unqualified name
+        let name = atom.rsplit('.').next().filter(|n| !n.is_empty())?;
+        kinds.get(name).or_else(|| kinds.get(atom)).cloned()
+    }
+}
+
+fn kinds_compatible(
+    declared: &str,
+    inferred: &str,
+    kind_variables: &mut HashMap<String, String>,
+) -> bool {
+    // Compares kinds argument by argument. Kind variables must be used consistently
+    if is_kind_variable(declared) {
+        return match kind_variables.get(declared) {
+            Some(k) => k == inferred,
+            None => {
+                kind_variables.insert(declared.to_string(), inferred.to_string());
+                true
+            }
+        };
+    }
+
+    let declared_parts = split_arrows(declared);
+    let inferred_parts = split_arrows(inferred);
+    if declared_parts.len() == 1 || declared_parts.len() != inferred_parts.len() {
+        return declared == inferred;
+    }
+
+    declared_parts
+        .iter()
+        .zip(inferred_parts.iter())
+        .all(|(d, i)| kinds_compatible(d, i, kind_variables))
+}
+
+fn is_kind_variable(kind: &str) -> bool {
+    kind.starts_with(char::is_lowercase) && kind.chars().all(|c| c.is_alphanumeric())
+}
+
+fn normalise_kind(kind: &str) -> String {
+    // `*` and `★` are old names for `Type` and `Natural` is the same kind as `Nat`
+    let kind = kind
+        .replace(['*', '★'], "Type")
+        .replace("Natural", "Nat")
+        .replace('→', "->");
+    split_arrows(&kind).join(" -> ")
+}
+
+fn split_arrows(kind: &str) -> Vec<String> {
+    // Splits a kind into its arguments and result at arrows outside of parentheses
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    let mut chars = kind.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '-' if depth == 0 && chars.peek() == Some(&'>') => {
+                chars.next();
+                parts.push(current.split_whitespace().collect::<Vec<&str>>().join(" "));
+                current = String::new();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current.split_whitespace().collect::<Vec<&str>>().join(" "));
+
+    // A kind wrapped in redundant parentheses is the same kind
+    parts
+        .into_iter()
+        .map(
+            |p| match p.strip_prefix('(').and_then(|p| p.strip_suffix(')')) {
+                Some(inner) if split_arrows(inner).len() == 1 => inner.to_string(),
+                _ => p,
+            },
+        )
+        .collect()
+}
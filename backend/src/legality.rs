@@ -0,0 +1,182 @@
+// Contains the logic for reading the language extensions enabled for a module and for checking
+// whether GHC would accept a suggested replacement with those extensions enabled
+
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::alias::get_terms;
+use crate::types::{Alias, Context, Legality, Term, TypeTree};
+
+// Extensions enabled by the GHC2021 and GHC2024 language editions which affect replacements
+const EDITION_EXTENSIONS: [&str; 3] = ["FlexibleInstances", "TypeSynonymInstances", "RankNTypes"];
+
+// Extensions which enable another extension
+const IMPLIED_EXTENSIONS: [(&str, &str); 2] = [
+    ("FlexibleInstances", "TypeSynonymInstances"),
+    ("ImpredicativeTypes", "RankNTypes"),
+];
+
+// Where a replaced type appears inside the type it was written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Result,
+    Domain,
+    Argument,
+}
+
+pub fn get_extensions(source: &str, defaults: &[String]) -> Vec<String> {
+    // Finds the extensions enabled for a module
+    // `LANGUAGE` pragmas in the module are applied after the package's default extensions
+    let re_pragma = Regex::new(r"(?is)\{-#\s*LANGUAGE\s+(.*?)#-\}").unwrap();
+    let pragmas: Vec<String> = re_pragma
+        .captures_iter(source)
+        .flat_map(|c| split_extensions(&c[1]))
+        .collect();
+
+    let mut enabled: Vec<String> = Vec::new();
+    for name in defaults.iter().chain(pragmas.iter()) {
+        let disabled = name
+            .strip_prefix("No")
+            .filter(|n| n.starts_with(char::is_uppercase));
+
+        if name == "GHC2021" || name == "GHC2024" {
+            enabled.extend(EDITION_EXTENSIONS.iter().map(|e| e.to_string()));
+        } else if let Some(disabled) = disabled {
+            enabled.retain(|e| e != disabled);
+        } else {
+            enabled.push(name.to_string());
+        }
+    }
+
+    for (extension, implied) in IMPLIED_EXTENSIONS {
+        if enabled.iter().any(|e| e == extension) {
+            enabled.push(implied.to_string());
+        }
+    }
+
+    enabled
+}
+
+pub fn read_cabal_extensions(cabal: &str) -> Vec<String> {
+    // Reads the `default-extensions` and `default-language` fields of a cabal file
+    // A field's value continues on following lines which are indented further than its name
+    let re_field = Regex::new(r"^(\s*)([\w-]+)\s*:(.*)$").unwrap();
+    let mut extensions = Vec::new();
+    let mut reading: Option<usize> = None;
+
+    for line in cabal.lines() {
+        let line = line.split("--").next().unwrap_or_default();
+
+        if let Some(field) = re_field.captures(line) {
+            let name = field[2].to_lowercase();
+            reading = None;
+            if ["default-extensions", "extensions", "default-language"].contains(&name.as_str()) {
+                reading = Some(field[1].len());
+                extensions.extend(split_extensions(&field[3]));
+            }
+        } else if let Some(indent) = reading {
+            if line.len() - line.trim_start().len() > indent {
+                extensions.extend(split_extensions(line));
+            } else if !line.trim().is_empty() {
+                reading = None;
+            }
+        }
+    }
+
+    extensions
+}
+
+fn split_extensions(text: &str) -> Vec<String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|e| !e.is_empty())
+        .map(|e| e.to_string())
+        .collect()
+}
+
+pub fn check_legality(
+    alias: &Alias,
+    variable_map: &HashMap<String, String>,
+    aliases: &[Alias],
+    context: Context,
+    slot: Slot,
+    replaced: &TypeTree,
+    extensions: &[String],
+) -> Legality {
+    // Decides if GHC accepts `alias` in place of the `replaced` type and, if not, which
+    // extensions it needs
+    let mut needed = Vec::new();
+
+    // Aliases which are passed to another alias without all of their arguments
+    let unsaturated = variable_map
+        .values()
+        .any(|t| aliases.iter().any(|a| a.name == *t && !a.params.is_empty()));
+    if unsaturated {
+        needed.push("LiberalTypeSynonyms");
+    }
+
+    let polymorphic = !alias.quantified.is_empty() || !alias.constraints.is_empty();
+    if context == Context::InstanceHead {
+        if polymorphic {
+            return Legality::Illegal(format!(
+                "{} contains a forall or a context which can't appear in an instance head",
+                alias.name
+            ));
+        }
+        if is_haskell98_head(replaced) {
+            needed.push("TypeSynonymInstances");
+        } else {
+            needed.push("FlexibleInstances");
+        }
+    } else if polymorphic {
+        // The fields of a constructor are its arguments
+        let slot = match context {
+            Context::RecordField | Context::ConstructorField if slot == Slot::Result => {
+                Slot::Domain
+            }
+            Context::TypeApplication => Slot::Argument,
+            _ => slot,
+        };
+        match slot {
+            Slot::Domain => needed.push("RankNTypes"),
+            Slot::Argument => needed.push("ImpredicativeTypes"),
+            Slot::Result => {}
+        }
+    }
+
+    let missing: Vec<String> = needed
+        .iter()
+        .filter(|n| !extensions.iter().any(|e| e == *n))
+        .map(|n| n.to_string())
+        .collect();
+
+    if missing.is_empty() {
+        Legality::Legal
+    } else {
+        Legality::NeedsExtensions(missing)
+    }
+}
+
+fn is_haskell98_head(tree: &TypeTree) -> bool {
+    // Haskell 98 instance heads are a type constructor applied to distinct type variables
+    let args = match tree.kind.as_str() {
+        "type_name" => return matches!(get_terms(tree)[..], [Term::Type(_)]),
+        "type_parens" => return tree.children.len() == 1 && is_haskell98_head(&tree.children[0]),
+        "type_apply" if matches!(get_terms(&tree.children[0])[..], [Term::Type(_)]) => {
+            &tree.children[1..]
+        }
+        "type_list" | "type_tuple" | "fun" => &tree.children[..],
+        _ => return false,
+    };
+
+    let mut seen = Vec::new();
+    args.iter().all(|arg| match &get_terms(arg)[..] {
+        [Term::Variable(v)] if arg.kind == "type_name" || arg.kind == "type_variable" => {
+            if seen.contains(v) {
+                return false;
+            }
+            seen.push(v.clone());
+            true
+        }
+        _ => false,
+    })
+}
@@ -2,10 +2,38 @@
 // Can be run in ongoing server mode or as single shot execution
 
 mod alias;
+mod apply;
+mod catalog;
+mod cpp;
+mod family;
+mod kind;
+mod legality;
+mod literate;
+mod near_miss;
+mod project;
+mod rewrite;
+mod scan;
+mod score;
+mod suggest;
 mod types;
+mod unused;
 
 pub use crate::alias::alias_replacement;
-pub use crate::types::{RequestAlias, ResponseMatches};
+pub use crate::apply::{apply_matches, unified_diff};
+pub use crate::catalog::catalog_aliases;
+use crate::legality::read_cabal_extensions;
+use crate::project::module_kind;
+pub use crate::project::project_matches;
+pub use crate::scan::scan_source;
+pub use crate::suggest::suggest_aliases;
+pub use crate::types::{
+    AliasSuggestion, CatalogEntry, Difference, Explanation, Failure, Legality, Match, NearMiss,
+    Options, ProjectFile, RequestAlias, RequestApply, RequestCatalog, RequestProject, RequestScan,
+    RequestSuggest, RequestUnused, ResponseApply, ResponseCatalog, ResponseMatches,
+    ResponseProject, ResponseScan, ResponseSuggest, ResponseUnused, Rewrite, SkippedMatch,
+    Specialisation, SuggestedOccurrence, TargetError, TargetMatches, UnusedAlias,
+};
+pub use crate::unused::unused_aliases;
 
 // Web framework
 use axum::{
@@ -13,17 +41,23 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use http::Method;
+use http::{Method, StatusCode};
 use tower_http::cors::{Any, CorsLayer};
 
 // File IO
-use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
 
 pub async fn start_web_server() {
     // Run a http server which responds to JSON on port 3000 with JSONified ResponseMatches
     let app = Router::new()
         .route("/api", post(get_matching_aliases))
+        .route("/scan", post(get_scan_matches))
+        .route("/project", post(get_project_matches))
+        .route("/apply", post(get_applied_source))
+        .route("/catalog", post(get_catalog))
+        .route("/unused", post(get_unused_aliases))
+        .route("/suggest", post(get_suggested_aliases))
         .route("/echo", get(echo))
         .layer(
             CorsLayer::new()
@@ -43,11 +77,35 @@ pub async fn start_web_server() {
 
 pub async fn get_matching_aliases(
     extract::Json(payload): extract::Json<RequestAlias>,
-) -> Json<ResponseMatches> {
+) -> Result<Json<ResponseMatches>, (StatusCode, Json<TargetError>)> {
     // Extract useful Request from json and run alias_replacement with it
+    // Targets which can't be parsed are a bad request
+    alias_replacement(payload)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(e)))
+}
+
+pub async fn get_scan_matches(
+    extract::Json(payload): extract::Json<RequestScan>,
+) -> Json<ResponseScan> {
+    // Check every type in the source against the aliases in the source
+    Json(scan_source(payload))
+}
+
+pub async fn get_project_matches(
+    extract::Json(payload): extract::Json<RequestProject>,
+) -> Result<Json<ResponseProject>, (StatusCode, Json<TargetError>)> {
+    // Analyse every module of a project together
+    project_matches(payload)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(e)))
+}
 
-    // dbg!(alias_replacement(payload.clone()));
-    Json(alias_replacement(payload))
+pub async fn get_applied_source(
+    extract::Json(payload): extract::Json<RequestApply>,
+) -> Json<ResponseApply> {
+    // Rewrite a source with the matches chosen by the client
+    Json(apply_matches(payload))
 }
 
 async fn echo(extract::Json(payload): extract::Json<RequestAlias>) -> Json<RequestAlias> {
@@ -55,13 +113,207 @@ async fn echo(extract::Json(payload): extract::Json<RequestAlias>) -> Json<Reque
     Json(payload)
 }
 
-pub fn run_on_file(path: PathBuf, target_type: String) -> ResponseMatches {
+pub fn run_on_file(
+    path: PathBuf,
+    target_type: String,
+    options: &Options,
+) -> Result<ResponseMatches, TargetError> {
     // Use alias replacement on a source file
-    let source = read_to_string(path).unwrap();
+    let source = read_to_string(&path).unwrap();
     let payload = RequestAlias {
         source,
         target_type,
+        targets: Vec::new(),
+        default_extensions: cabal_extensions(&path),
+        literate: is_literate(&path),
+        defines: options.defines.clone(),
+        best: options.best,
+        explain: options.explain.clone(),
+        near_miss: options.near_miss,
     };
 
     alias_replacement(payload)
 }
+
+pub fn run_targets_on_file(
+    path: PathBuf,
+    targets: Vec<String>,
+    options: &Options,
+) -> Result<ResponseMatches, TargetError> {
+    // Use alias replacement with several target types on a source file, parsing it only once
+    let source = read_to_string(&path).unwrap();
+    let payload = RequestAlias {
+        source,
+        target_type: String::new(),
+        targets,
+        default_extensions: cabal_extensions(&path),
+        literate: is_literate(&path),
+        defines: options.defines.clone(),
+        best: options.best,
+        explain: options.explain.clone(),
+        near_miss: options.near_miss,
+    };
+
+    alias_replacement(payload)
+}
+
+pub async fn get_catalog(
+    extract::Json(payload): extract::Json<RequestCatalog>,
+) -> Json<ResponseCatalog> {
+    // Compare the aliases declared across the files with each other
+    Json(catalog_aliases(payload))
+}
+
+pub async fn get_unused_aliases(
+    extract::Json(payload): extract::Json<RequestUnused>,
+) -> Json<ResponseUnused> {
+    // Find the aliases of a project which are never referenced
+    Json(unused_aliases(payload))
+}
+
+pub async fn get_suggested_aliases(
+    extract::Json(payload): extract::Json<RequestSuggest>,
+) -> Json<ResponseSuggest> {
+    // Suggest aliases for the types written out repeatedly in a project
+    Json(suggest_aliases(payload))
+}
+
+pub fn scan_file(path: PathBuf, options: &Options) -> ResponseScan {
+    // Use scan mode on a source file
+    let source = read_to_string(&path).unwrap();
+
+    scan_source(RequestScan {
+        source,
+        default_extensions: cabal_extensions(&path),
+        literate: is_literate(&path),
+        defines: options.defines.clone(),
+        best: options.best,
+    })
+}
+
+pub fn fix_file(path: PathBuf, options: &Options) -> ResponseApply {
+    // Scans a source file and applies the replacements chosen by rewriting each type in it: the
+    // non-overlapping set with the highest total score, using only replacements which GHC accepts
+    // without further extensions and which are away from parse errors
+    // The other such replacements overlap a chosen one, so are given after them to be skipped
+    let scan = scan_file(path, options);
+    let mut matches: Vec<Match> = scan.rewrites.into_iter().flat_map(|r| r.edits).collect();
+    let others: Vec<Match> = scan
+        .matches
+        .into_iter()
+        .filter(|m| m.legality == Legality::Legal && !m.low_confidence && m.score > 0.0)
+        .filter(|m| !matches.contains(m))
+        .collect();
+    matches.extend(others);
+
+    apply_matches(RequestApply {
+        source: scan.echo_request.source,
+        matches,
+        literate: scan.echo_request.literate,
+    })
+}
+
+pub fn run_on_project(
+    dir: PathBuf,
+    target_type: Option<String>,
+    options: &Options,
+) -> Result<ResponseProject, TargetError> {
+    // Use project mode on every Haskell file below a directory
+    project_matches(RequestProject {
+        files: read_project_files(&dir),
+        target_type,
+        default_extensions: cabal_extensions(&dir),
+        defines: options.defines.clone(),
+        best: options.best,
+    })
+}
+
+pub fn catalog_path(path: PathBuf, options: &Options) -> ResponseCatalog {
+    // Compare the aliases of a source file, or of every Haskell file below a directory
+    catalog_aliases(RequestCatalog {
+        files: read_project_files(&path),
+        default_extensions: cabal_extensions(&path),
+        defines: options.defines.clone(),
+    })
+}
+
+pub fn unused_path(path: PathBuf, options: &Options) -> ResponseUnused {
+    // Find the aliases of a source file, or of every Haskell file below a directory, which are
+    // never referenced
+    unused_aliases(RequestUnused {
+        files: read_project_files(&path),
+        default_extensions: cabal_extensions(&path),
+        defines: options.defines.clone(),
+    })
+}
+
+pub fn suggest_path(path: PathBuf, options: &Options) -> ResponseSuggest {
+    // Suggest aliases for the types written out repeatedly in a source file, or in every
+    // Haskell file below a directory
+    suggest_aliases(RequestSuggest {
+        files: read_project_files(&path),
+        default_extensions: cabal_extensions(&path),
+        defines: options.defines.clone(),
+    })
+}
+
+fn read_project_files(path: &Path) -> Vec<ProjectFile> {
+    // Reads a source file, or every Haskell file below a directory in order of their paths
+    let mut paths = Vec::new();
+    if path.is_dir() {
+        find_haskell_files(path, &mut paths);
+        paths.sort();
+    } else {
+        paths.push(path.to_path_buf());
+    }
+
+    paths
+        .into_iter()
+        .map(|path| ProjectFile {
+            source: read_to_string(&path).unwrap(),
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect()
+}
+
+fn find_haskell_files(dir: &Path, paths: &mut Vec<PathBuf>) {
+    // Build directories and hidden directories are skipped
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "dist-newstyle" {
+                find_haskell_files(&path, paths);
+            }
+        } else if module_kind(&name).is_some() {
+            paths.push(path);
+        }
+    }
+}
+
+fn is_literate(path: &Path) -> bool {
+    module_kind(&path.to_string_lossy()).is_some_and(|(_, literate)| literate)
+}
+
+fn cabal_extensions(path: &Path) -> Vec<String> {
+    // Default extensions come from the nearest cabal file in a directory containing `path`
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    for dir in path.ancestors() {
+        let cabal = read_dir(dir).ok().and_then(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|e| e.path())
+                .find(|p| p.extension().is_some_and(|e| e == "cabal"))
+        });
+        if let Some(cabal) = cabal {
+            return read_cabal_extensions(&read_to_string(cabal).unwrap_or_default());
+        }
+    }
+
+    Vec::new()
+}
@@ -0,0 +1,32 @@
+// Contains the logic for reading literate Haskell files, written with either bird tracks or
+// LaTeX style code blocks, as the Haskell source they contain
+
+pub fn unliterate(source: &str) -> String {
+    // Blanks every line which isn't code and replaces each bird track with a space
+    // Code keeps the row and column it had in the literate file so every position found in the
+    // result is also a position in the literate file, and replacements can be applied to it
+    // directly
+    let mut in_code_block = false;
+
+    source
+        .split('\n')
+        .map(|line| {
+            let directive = line.trim_start();
+            if in_code_block {
+                if directive.starts_with("\\end{code}") {
+                    in_code_block = false;
+                    return String::new();
+                }
+                line.to_string()
+            } else if directive.starts_with("\\begin{code}") {
+                in_code_block = true;
+                String::new()
+            } else if let Some(code) = line.strip_prefix('>') {
+                format!(" {}", code)
+            } else {
+                String::new()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -1,11 +1,16 @@
 // Simple CLI interface for functions provided in htar
 
-use htar::{run_on_file, start_web_server};
+use htar::{
+    catalog_path, fix_file, run_on_file, run_on_project, run_targets_on_file, scan_file,
+    start_web_server, suggest_path, unified_diff, unused_path, Options, TargetError,
+};
 
 // CLI library
 use clap::Parser as CLIParser;
 
+use std::fs::{copy, write};
 use std::path::PathBuf;
+use std::process::exit;
 
 #[derive(CLIParser)]
 #[clap(author, version, about, long_about = None)]
@@ -14,13 +19,63 @@ struct Args {
     #[clap(short, long, action)]
     server: bool,
 
-    /// Path of Haskell file to analyse
+    /// Path of Haskell file to analyse, or of a project directory to analyse every module in
     #[clap(short, long, value_parser, value_name = "FILE")]
     path: Option<PathBuf>,
 
-    /// Target type alias
+    /// Target type alias. Can be given several times to match every target in one run
     #[clap(short, long, value_parser, value_name = "TYPE")]
-    target: Option<String>,
+    target: Vec<String>,
+
+    /// Checks every type in the file against its aliases instead of a target type
+    #[clap(long, action)]
+    scan: bool,
+
+    /// Defines for files using CPP, given as NAME or NAME=VALUE. Without any, every branch of
+    /// each conditional is analysed
+    #[clap(
+        short = 'D',
+        long = "define",
+        value_parser,
+        value_name = "NAME[=VALUE]"
+    )]
+    defines: Vec<String>,
+
+    /// Only gives the highest scoring match for each target or scanned type
+    #[clap(long, action)]
+    best: bool,
+
+    /// Explains why each alias with this name does or doesn't match the target
+    #[clap(long, value_parser, value_name = "ALIAS")]
+    explain: Option<String>,
+
+    /// Also reports aliases which differ from the target in at most this many terms
+    #[clap(long, value_parser, value_name = "DISTANCE")]
+    near_miss: Option<usize>,
+
+    /// Lists aliases of the file or project which are equivalent or instances of other aliases
+    #[clap(long, action)]
+    catalog: bool,
+
+    /// Lists aliases of the file or project which are never referenced
+    #[clap(long, action)]
+    unused: bool,
+
+    /// Suggests new aliases for the types written out repeatedly in the file or project
+    #[clap(long, action)]
+    suggest: bool,
+
+    /// Rewrites the file in place with every replacement found by scanning it which GHC accepts
+    #[clap(long, action)]
+    fix: bool,
+
+    /// Keeps a copy of the original file with a `.orig` suffix when using --fix
+    #[clap(long, action, requires = "fix")]
+    backup: bool,
+
+    /// Prints the replacements found by scanning the file as a unified diff
+    #[clap(long, action)]
+    diff: bool,
 
     /// Enable human readable output
     #[clap(short = 'r', long, action)]
@@ -30,20 +85,122 @@ struct Args {
 #[tokio::main]
 async fn main() {
     let args = Args::parse();
+    let options = Options {
+        defines: (!args.defines.is_empty()).then_some(args.defines),
+        best: args.best,
+        explain: args.explain,
+        near_miss: args.near_miss,
+    };
 
     if args.server {
         start_web_server().await;
     } else {
         // Run in command line mode
-        match (args.path, args.target) {
+        match (args.path, &args.target[..]) {
             (None, _) => {
                 println!("Missing path to source file (use -p)")
             }
-            (_, None) => {
+            (Some(path), _) if args.catalog => {
+                let catalog_data = catalog_path(path, &options);
+
+                if args.human_readable {
+                    println!("{}", catalog_data)
+                } else {
+                    println!("{}", serde_json::to_string(&catalog_data).unwrap())
+                }
+            }
+            (Some(path), _) if args.unused => {
+                let unused_data = unused_path(path, &options);
+
+                if args.human_readable {
+                    println!("{}", unused_data)
+                } else {
+                    println!("{}", serde_json::to_string(&unused_data).unwrap())
+                }
+            }
+            (Some(path), _) if args.suggest => {
+                let suggest_data = suggest_path(path, &options);
+
+                if args.human_readable {
+                    println!("{}", suggest_data)
+                } else {
+                    println!("{}", serde_json::to_string(&suggest_data).unwrap())
+                }
+            }
+            (Some(path), _) if (args.fix || args.diff) && path.is_dir() => {
+                eprintln!("--fix and --diff take a single file");
+                exit(1)
+            }
+            (Some(path), _) if args.fix || args.diff => {
+                let applied = fix_file(path.clone(), &options);
+
+                if args.diff {
+                    let name = path.to_string_lossy();
+                    print!(
+                        "{}",
+                        unified_diff(&applied.echo_request.source, &applied.source, &name)
+                    )
+                } else if args.human_readable {
+                    println!("{}", applied)
+                } else {
+                    println!("{}", serde_json::to_string(&applied).unwrap())
+                }
+
+                if args.fix {
+                    if args.backup {
+                        let mut backup = path.clone().into_os_string();
+                        backup.push(".orig");
+                        copy(&path, backup).unwrap();
+                    }
+                    write(&path, &applied.source).unwrap();
+                }
+            }
+            (Some(path), [_, _, ..]) if path.is_dir() => {
+                eprintln!("Project mode takes a single target type");
+                exit(1)
+            }
+            (Some(path), targets) if path.is_dir() => {
+                let target_type = targets.first().cloned();
+                let project_data = match run_on_project(path, target_type, &options) {
+                    Ok(project_data) => project_data,
+                    Err(e) => exit_with_error(e, args.human_readable),
+                };
+
+                if args.human_readable {
+                    println!("{}", project_data)
+                } else {
+                    println!("{}", serde_json::to_string(&project_data).unwrap())
+                }
+            }
+            (Some(path), _) if args.scan => {
+                let scan_data = scan_file(path, &options);
+
+                if args.human_readable {
+                    println!("{}", scan_data)
+                } else {
+                    println!("{}", serde_json::to_string(&scan_data).unwrap())
+                }
+            }
+            (_, []) => {
                 println!("Missing target type (use -t)")
             }
-            (Some(path), Some(target_type)) => {
-                let replacement_data = run_on_file(path, target_type);
+            (Some(path), [target_type]) => {
+                let replacement_data = match run_on_file(path, target_type.clone(), &options) {
+                    Ok(replacement_data) => replacement_data,
+                    Err(e) => exit_with_error(e, args.human_readable),
+                };
+
+                if args.human_readable {
+                    println!("{}", replacement_data)
+                } else {
+                    println!("{}", serde_json::to_string(&replacement_data).unwrap())
+                }
+            }
+            (Some(path), targets) => {
+                let replacement_data = match run_targets_on_file(path, targets.to_vec(), &options) {
+                    Ok(replacement_data) => replacement_data,
+                    Err(e) => exit_with_error(e, args.human_readable),
+                };
 
                 if args.human_readable {
                     println!("{}", replacement_data)
@@ -54,3 +211,13 @@ async fn main() {
         }
     }
 }
+
+fn exit_with_error(error: TargetError, human_readable: bool) -> ! {
+    // Reports a target type which can't be parsed and exits with a failure
+    if human_readable {
+        eprintln!("{}", error)
+    } else {
+        println!("{}", serde_json::to_string(&error).unwrap())
+    }
+    exit(1)
+}
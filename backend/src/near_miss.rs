@@ -0,0 +1,117 @@
+// Contains the logic for finding aliases which almost replace a target, differing from it in a
+// few positions, and for naming the positions where they differ
+
+use std::collections::HashMap;
+
+use crate::alias::{get_terms, match_constraint_alias, try_match_alias, TARGET_PREFIX};
+use crate::family::is_variable;
+use crate::types::{Alias, Difference, NearMiss, Target, TypeTree};
+
+pub(crate) fn find_near_misses(
+    target_type: &str,
+    target: &Target,
+    aliases: &[Alias],
+    kinds: &HashMap<String, String>,
+    max_distance: usize,
+) -> Vec<NearMiss> {
+    // Finds the aliases which don't replace the target but are at most `max_distance` from it
+    // Aliases which replace the target are exact matches and aren't near misses
+    // Closest aliases come first
+    let size = term_count(&target.body);
+
+    let mut near_misses: Vec<NearMiss> = aliases
+        .iter()
+        .filter(|alias| {
+            try_match_alias(target, alias, kinds).is_err()
+                && match_constraint_alias(target, alias, kinds).is_none()
+        })
+        .filter_map(|alias| {
+            let mut differences = Vec::new();
+            compare(
+                &alias.rhs,
+                &target.body,
+                &mut HashMap::new(),
+                &mut differences,
+            );
+            let distance: usize = differences.iter().map(|(_, cost)| cost).sum();
+
+            // An alias which differs from the whole target has nothing in common with it
+            if distance == 0 || distance > max_distance || distance >= size {
+                return None;
+            }
+
+            Some(NearMiss {
+                alias: alias.name.clone(),
+                declaration: alias.declaration.clone(),
+                location: alias.location.clone(),
+                origin: alias.origin.clone(),
+                distance,
+                differences: differences
+                    .into_iter()
+                    .map(|((alias_part, target_part), _)| Difference {
+                        alias: alias_part,
+                        column: column(target_type, target_part),
+                        target: target_part.text.clone(),
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    near_misses.sort_by_key(|n| n.distance);
+    near_misses
+}
+
+fn compare<'a>(
+    alias: &TypeTree,
+    target: &'a TypeTree,
+    variable_map: &mut HashMap<String, String>,
+    differences: &mut Vec<((String, &'a TypeTree), usize)>,
+) {
+    // Walks an alias and a target together, recording the outermost parts which differ along
+    // with how many terms each difference covers
+    // A type variable of the alias can stand for any part of the target, but must stand for
+    // the same part everywhere it appears
+    let mut differ = |alias_part: String, alias_terms: usize| {
+        let cost = alias_terms.max(term_count(target)).max(1);
+        differences.push(((alias_part, target), cost));
+    };
+
+    if is_variable(alias) {
+        let bound = variable_map
+            .entry(alias.text.clone())
+            .or_insert_with(|| target.text.clone());
+        if *bound != target.text {
+            differ(alias.text.clone(), 1);
+        }
+        return;
+    }
+
+    let node = |t: &TypeTree| match t.kind.as_str() {
+        "type_literal" => "type_name".to_string(),
+        kind => kind.to_string(),
+    };
+    if node(alias) != node(target) || alias.children.len() != target.children.len() {
+        differ(alias.text.clone(), term_count(alias));
+    } else if node(alias) == "type_name" || alias.children.is_empty() {
+        if alias.text != target.text {
+            differ(alias.text.clone(), 1);
+        }
+    } else {
+        for (a, t) in alias.children.iter().zip(target.children.iter()) {
+            compare(a, t, variable_map, differences);
+        }
+    }
+}
+
+fn term_count(tree: &TypeTree) -> usize {
+    get_terms(tree).len()
+}
+
+fn column(target_type: &str, part: &TypeTree) -> usize {
+    // Column of a part of the target in the text of the target, counted in characters from 0
+    let offset = part.start_byte.saturating_sub(TARGET_PREFIX.len());
+    target_type
+        .get(..offset)
+        .map_or(0, |before| before.chars().count())
+}
@@ -0,0 +1,335 @@
+// Contains the logic for analysing the modules of a project together. Each module can use the
+// aliases of the modules it imports, including those declared in `hs-boot` files and Backpack
+// `hsig` signatures
+
+use regex::Regex;
+
+use crate::alias::{
+    get_aliases, get_fixities, haskell_parser, normalise_syntax, replace_with_imports,
+    strip_comments, validate_target,
+};
+use crate::cpp::preprocess;
+use crate::family::get_families;
+use crate::literate::unliterate;
+use crate::scan::scan_with_imports;
+use crate::types::{
+    Alias, ModuleMatches, Origin, ProjectFile, RequestAlias, RequestProject, RequestScan,
+    ResponseProject, TargetError,
+};
+
+// The kinds of file a module can be declared in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ModuleKind {
+    Source,
+    Boot,
+    Signature,
+}
+
+// A module of the project with the declarations other modules can import
+// `source` is the Haskell in the file, with literate files already read
+pub(crate) struct Module<'a> {
+    pub(crate) name: String,
+    pub(crate) kind: ModuleKind,
+    pub(crate) literate: bool,
+    pub(crate) file: &'a ProjectFile,
+    pub(crate) source: String,
+    pub(crate) aliases: Vec<Alias>,
+    abstract_types: Vec<String>,
+    imports: Vec<Import>,
+}
+
+// `names` are the names listed after the module, which are either the only names imported or,
+// with `hiding`, the names which aren't
+struct Import {
+    module: String,
+    source: bool,
+    qualifier: Option<String>,
+    names: Option<Vec<String>>,
+    hiding: bool,
+}
+
+impl Module<'_> {
+    pub(crate) fn imports_module(&self, name: &str) -> bool {
+        self.imports.iter().any(|i| i.module == name)
+    }
+}
+
+pub fn project_matches(request: RequestProject) -> Result<ResponseProject, TargetError> {
+    // Public API function to analyse every module of a project
+    // Details on RequestProject and ResponseProject can be found in types.rs
+    if let Some(target_type) = &request.target_type {
+        validate_target(target_type)?;
+    }
+
+    let modules: Vec<Module> = request
+        .files
+        .iter()
+        .filter_map(|file| read_module(file, &request.default_extensions, &request.defines))
+        .collect();
+
+    // Boot files and signatures only provide declarations to other modules
+    let results = modules
+        .iter()
+        .filter(|m| m.kind == ModuleKind::Source)
+        .map(|module| {
+            let imported = imported_aliases(module, &modules);
+            let (matches, parse_errors) = match &request.target_type {
+                Some(target_type) => {
+                    let request = RequestAlias {
+                        target_type: target_type.clone(),
+                        targets: Vec::new(),
+                        source: module.file.source.clone(),
+                        default_extensions: request.default_extensions.clone(),
+                        literate: module.literate,
+                        defines: request.defines.clone(),
+                        best: request.best,
+                        explain: None,
+                        near_miss: None,
+                    };
+                    let response = replace_with_imports(request, &imported);
+                    (response.matches, response.parse_errors)
+                }
+                None => {
+                    let request = RequestScan {
+                        source: module.file.source.clone(),
+                        default_extensions: request.default_extensions.clone(),
+                        literate: module.literate,
+                        defines: request.defines.clone(),
+                        best: request.best,
+                    };
+                    let response = scan_with_imports(request, &imported);
+                    (response.matches, response.parse_errors)
+                }
+            };
+
+            ModuleMatches {
+                module: module.name.clone(),
+                path: module.file.path.clone(),
+                matches,
+                parse_errors,
+            }
+        })
+        .collect();
+
+    Ok(ResponseProject {
+        echo_request: request,
+        modules: results,
+    })
+}
+
+pub(crate) fn module_kind(path: &str) -> Option<(ModuleKind, bool)> {
+    // Reads the kind of module a file holds, and whether it is literate, from its extension
+    match path.rsplit_once('.')?.1 {
+        "hs" => Some((ModuleKind::Source, false)),
+        "lhs" => Some((ModuleKind::Source, true)),
+        "hs-boot" => Some((ModuleKind::Boot, false)),
+        "lhs-boot" => Some((ModuleKind::Boot, true)),
+        "hsig" => Some((ModuleKind::Signature, false)),
+        "lhsig" => Some((ModuleKind::Signature, true)),
+        _ => None,
+    }
+}
+
+pub(crate) fn read_module<'a>(
+    file: &'a ProjectFile,
+    default_extensions: &[String],
+    defines: &Option<Vec<String>>,
+) -> Option<Module<'a>> {
+    // Reads the name, aliases, abstract types and imports of a module
+    let (kind, literate) = module_kind(&file.path)?;
+
+    let source = if literate {
+        unliterate(&file.source)
+    } else {
+        file.source.clone()
+    };
+
+    // Modules without a header are named after their file
+    let re_header = Regex::new(r"(?m)^(module|signature)\s+([A-Z][\w.]*)").unwrap();
+    let name = match re_header.captures(&source) {
+        Some(c) => c[2].to_string(),
+        None => {
+            let file_name = file.path.rsplit(['/', '\\']).next()?;
+            file_name.split('.').next()?.to_string()
+        }
+    };
+
+    // Aliases declared in any branch of a CPP conditional can be imported
+    let mut aliases: Vec<Alias> = Vec::new();
+    for variant in preprocess(&source, default_extensions, defines) {
+        for alias in read_aliases(&variant) {
+            if !aliases
+                .iter()
+                .any(|a| a.name == alias.name && a.location == alias.location)
+            {
+                aliases.push(alias);
+            }
+        }
+    }
+
+    // Types declared without constructors in a boot file or signature
+    let re_abstract = Regex::new(r"(?m)^data\s+([A-Z][\w']*)[\w' \t]*$").unwrap();
+    let abstract_types = re_abstract
+        .captures_iter(&source)
+        .map(|c| c[1].to_string())
+        .collect();
+
+    Some(Module {
+        name,
+        kind,
+        literate,
+        file,
+        aliases,
+        abstract_types,
+        imports: read_imports(&source),
+        source,
+    })
+}
+
+fn read_aliases(source: &str) -> Vec<Alias> {
+    // Initialise treesitter
+    let mut parser = haskell_parser();
+
+    let fixities = get_fixities(source);
+    let tree = parser.parse(normalise_syntax(source), None).unwrap();
+    let families = get_families(&tree.root_node(), source.as_bytes(), &fixities);
+
+    get_aliases(&tree.root_node(), source.as_bytes(), &fixities, &families)
+}
+
+fn read_imports(source: &str) -> Vec<Import> {
+    // Reads imports such as `import {-# SOURCE #-} qualified Data.Map as M (Map)`, with
+    // `qualified` either before or after the module name
+    let re_import = Regex::new(
+        r"(?m)^import\s+(\{-#\s*SOURCE\s*#-\}\s*)?(qualified\s+)?([A-Z][\w.]*)(\s+qualified)?(\s+as\s+([A-Z][\w.]*))?",
+    )
+    .unwrap();
+    let source = strip_comments(source);
+
+    re_import
+        .captures_iter(&source)
+        .map(|c| {
+            let qualified = c.get(2).is_some() || c.get(4).is_some();
+            let name = c.get(6).map_or(&c[3], |a| a.as_str());
+            let (names, hiding) = read_import_list(&source[c.get(0).unwrap().end()..]);
+            Import {
+                module: c[3].to_string(),
+                source: c.get(1).is_some(),
+                qualifier: qualified.then(|| name.to_string()),
+                names,
+                hiding,
+            }
+        })
+        .collect()
+}
+
+fn read_import_list(after: &str) -> (Option<Vec<String>>, bool) {
+    // Reads the list after an import such as `(Table, type (:->), Node(..))` or
+    // `hiding (Table)`. The constructors and methods listed with a name are skipped
+    let after = after.trim_start();
+    let (hiding, after) = match after.strip_prefix("hiding") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, after),
+    };
+    if !after.starts_with('(') {
+        return (None, false);
+    }
+
+    // The list ends at the bracket closing the first one
+    let mut depth = 0;
+    let mut items = vec![String::new()];
+    for c in after.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        match (depth, c) {
+            (0, _) => break,
+            (1, '(') => {}
+            (1, ',') => items.push(String::new()),
+            _ => items.last_mut().unwrap().push(c),
+        }
+    }
+
+    let names = items
+        .iter()
+        .filter_map(|item| {
+            let item = item.trim();
+            let item = item
+                .strip_prefix("type ")
+                .or_else(|| item.strip_prefix("pattern "))
+                .unwrap_or(item)
+                .trim_start();
+            let name = match item.strip_prefix('(') {
+                Some(operator) => operator.split(')').next()?,
+                None => item.split(|c: char| c == '(' || c.is_whitespace()).next()?,
+            };
+            (!name.is_empty()).then(|| name.trim().to_string())
+        })
+        .collect();
+    (Some(names), hiding)
+}
+
+fn imported_aliases(module: &Module, modules: &[Module]) -> Vec<Alias> {
+    // Collects the aliases a module can use from the modules it imports
+    let mut imported = Vec::new();
+
+    for import in module.imports.iter() {
+        // `{-# SOURCE #-}` imports read the boot file of a module. Other imports read a Backpack
+        // signature in preference to the module implementing it
+        let kinds = if import.source {
+            vec![ModuleKind::Boot]
+        } else {
+            vec![ModuleKind::Signature, ModuleKind::Source]
+        };
+        let found = match kinds.iter().find_map(|kind| {
+            modules
+                .iter()
+                .find(|m| m.name == import.module && m.kind == *kind)
+        }) {
+            Some(found) => found,
+            None => continue,
+        };
+
+        let implementation = modules.iter().find(|m| {
+            found.kind != ModuleKind::Source && m.name == found.name && m.kind == ModuleKind::Source
+        });
+
+        // An abstract type in a boot file or signature can be implemented by a synonym
+        let mut aliases = found.aliases.clone();
+        if let Some(implementation) = implementation {
+            aliases.extend(
+                implementation
+                    .aliases
+                    .iter()
+                    .filter(|a| found.abstract_types.contains(&a.name))
+                    .cloned(),
+            );
+        }
+
+        for alias in aliases {
+            // Only the names listed are imported, or with `hiding` every name but them
+            let names = import.names.as_ref();
+            if names.is_some_and(|n| n.contains(&alias.name) == import.hiding) {
+                continue;
+            }
+
+            let name = match &import.qualifier {
+                Some(qualifier) => format!("{}.{}", qualifier, alias.name),
+                None => alias.name.clone(),
+            };
+            imported.push(Alias {
+                name,
+                origin: Some(Origin {
+                    module: found.name.clone(),
+                    path: found.file.path.clone(),
+                    implemented_in: implementation.map(|m| m.file.path.clone()),
+                }),
+                ..alias
+            });
+        }
+    }
+
+    imported
+}
@@ -0,0 +1,103 @@
+// Contains the logic for rewriting a whole type with the set of replacements for the types
+// inside it which has the highest total score and in which no two replacements overlap
+
+use crate::apply::apply_matches;
+use crate::types::{Legality, Match, Occurrence, Position, RequestApply, Rewrite};
+
+pub(crate) fn rewrite_occurrence(whole: &Occurrence, matches: &[Match]) -> Option<Rewrite> {
+    // Chooses replacements for `whole` and the types inside it and rewrites it with them
+    // Only replacements which GHC accepts, which are away from parse errors and which score
+    // better than leaving the type as it is are used
+    let mut candidates: Vec<(usize, usize, &Match)> = matches
+        .iter()
+        .filter(|m| m.legality == Legality::Legal && !m.low_confidence && m.score > 0.0)
+        .filter_map(|m| {
+            let location = &m.occurrence.as_ref()?.location;
+            let start = offset(whole, &relative(whole, &location.start)?)?;
+            let end = offset(whole, &relative(whole, &location.end)?)?;
+            Some((start, end, m))
+        })
+        .collect();
+    candidates.sort_by_key(|(_, end, _)| *end);
+
+    // Weighted interval scheduling. `best[i]` is the highest total score of the first `i`
+    // candidates and `previous[i]` is the number of candidates which end before candidate `i`
+    // starts
+    let mut best = vec![0.0; candidates.len() + 1];
+    let mut previous = Vec::new();
+    let mut taken = Vec::new();
+    for (i, (start, _, m)) in candidates.iter().enumerate() {
+        let p = candidates[..i]
+            .iter()
+            .rposition(|(_, end, _)| end <= start)
+            .map_or(0, |j| j + 1);
+        let take = best[p] + m.score > best[i];
+        best[i + 1] = if take { best[p] + m.score } else { best[i] };
+        previous.push(p);
+        taken.push(take);
+    }
+
+    let mut edits = Vec::new();
+    let mut i = candidates.len();
+    while i > 0 {
+        if taken[i - 1] {
+            edits.push(candidates[i - 1].2.clone());
+            i = previous[i - 1];
+        } else {
+            i -= 1;
+        }
+    }
+    if edits.is_empty() {
+        return None;
+    }
+    edits.reverse();
+
+    // The edits are applied to the text of the whole type, so their positions are moved to be
+    // relative to its start
+    let relative_edits = edits
+        .iter()
+        .filter_map(|m| {
+            let mut m = m.clone();
+            let occurrence = m.occurrence.as_mut()?;
+            occurrence.location.start = relative(whole, &occurrence.location.start)?;
+            occurrence.location.end = relative(whole, &occurrence.location.end)?;
+            Some(m)
+        })
+        .collect();
+    let rewritten = apply_matches(RequestApply {
+        source: whole.text.clone(),
+        matches: relative_edits,
+        literate: false,
+    })
+    .source;
+
+    Some(Rewrite {
+        occurrence: whole.clone(),
+        rewritten,
+        score: best[candidates.len()],
+        edits,
+    })
+}
+
+fn relative(whole: &Occurrence, position: &Position) -> Option<Position> {
+    // Moves a position in the source to the same position in the text of `whole`
+    let start = &whole.location.start;
+    let row = position.row.checked_sub(start.row)?;
+    let col = if row == 0 {
+        position.col.checked_sub(start.col)?
+    } else {
+        position.col
+    };
+    Some(Position { row, col })
+}
+
+fn offset(whole: &Occurrence, position: &Position) -> Option<usize> {
+    // Byte offset of a relative position in the text of `whole`
+    let line_start: usize = whole
+        .text
+        .split('\n')
+        .take(position.row)
+        .map(|l| l.len() + 1)
+        .sum();
+    Some(line_start + position.col).filter(|o| *o <= whole.text.len())
+}
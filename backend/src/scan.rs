@@ -0,0 +1,413 @@
+// Contains the logic for scanning a source file for types written in declarations and matching
+// every one of them, and the types inside them, against the aliases declared in the source
+
+use std::collections::HashMap;
+
+// Treesitter
+use tree_sitter::Node as TSNode;
+
+use crate::alias::{
+    flag_low_confidence, get_aliases, get_fixities, get_parse_errors, get_terms, haskell_parser,
+    match_alias, match_constraint_alias, normalise_syntax, render_syntax, resolve_fixity,
+    target_from_tree, to_type_tree, uses_unicode_syntax,
+};
+use crate::cpp::preprocess;
+use crate::family::get_families;
+use crate::kind::get_kinds;
+use crate::legality::{check_legality, get_extensions, Slot};
+use crate::literate::unliterate;
+use crate::rewrite::rewrite_occurrence;
+use crate::score::{best_matches, score_match};
+use crate::types::{
+    Alias, Context, Fixity, Match, Occurrence, ParseError, RequestScan, ResponseScan, Rewrite,
+    Term, TypeTree,
+};
+
+// Children of a data constructor which aren't the types of its fields. Record fields are found
+// separately
+const NOT_FIELDS: [&str; 7] = [
+    "constructor",
+    "constructor_operator",
+    "pragma",
+    "comment",
+    "record_fields",
+    "forall",
+    "context",
+];
+
+// Syntax in which the whole type written is applied to by something else, such as a class in an
+// instance head or a constructor in one of its fields
+const APPLIED_CONTEXTS: [Context; 3] = [
+    Context::InstanceHead,
+    Context::ConstructorField,
+    Context::TypeApplication,
+];
+
+pub fn scan_source(request: RequestScan) -> ResponseScan {
+    // Public API function to find every type in a source file which could use an alias
+    // Details on RequestScan and ResponseScan can be found in types.rs
+    scan_with_imports(request, &[])
+}
+
+pub(crate) fn scan_with_imports(request: RequestScan, imported: &[Alias]) -> ResponseScan {
+    // Scans a source file using its own aliases and those imported from other modules of a
+    // project
+
+    // Source text
+    // Literate files are read as the Haskell they contain, which keeps every position the same
+    let source = if request.literate {
+        unliterate(&request.source)
+    } else {
+        request.source.clone()
+    };
+
+    // Types and parse errors in code shared by several versions of a source using CPP are only
+    // reported once
+    let mut matches: Vec<Match> = Vec::new();
+    let mut rewrites: Vec<Rewrite> = Vec::new();
+    let mut parse_errors: Vec<ParseError> = Vec::new();
+    for variant in preprocess(&source, &request.default_extensions, &request.defines) {
+        let (variant_matches, variant_rewrites, variant_errors) =
+            scan_variant(&request, &variant, imported);
+        for m in variant_matches {
+            if !matches.contains(&m) {
+                matches.push(m);
+            }
+        }
+        for r in variant_rewrites {
+            if !rewrites.contains(&r) {
+                rewrites.push(r);
+            }
+        }
+        for e in variant_errors {
+            if !parse_errors.contains(&e) {
+                parse_errors.push(e);
+            }
+        }
+    }
+
+    if request.best {
+        matches = best_matches(matches);
+    }
+
+    ResponseScan {
+        echo_request: request,
+        matches,
+        rewrites,
+        parse_errors,
+    }
+}
+
+fn scan_variant(
+    request: &RequestScan,
+    source: &str,
+    imported: &[Alias],
+) -> (Vec<Match>, Vec<Rewrite>, Vec<ParseError>) {
+    // Scans one version of the source
+
+    // Initialise treesitter
+    let mut parser = haskell_parser();
+
+    let source_bytes = source.as_bytes();
+    let fixities = get_fixities(source);
+
+    // Create AST
+    let tree = parser.parse(normalise_syntax(source), None).unwrap();
+    let root = tree.root_node();
+    let parse_errors = get_parse_errors(&root, source_bytes);
+
+    let families = get_families(&root, source_bytes, &fixities);
+    let kinds = get_kinds(source);
+    let extensions = get_extensions(source, &request.default_extensions);
+
+    // An alias for a lone type variable would match every type in the file
+    let aliases: Vec<Alias> = get_aliases(&root, source_bytes, &fixities, &families)
+        .into_iter()
+        .chain(imported.iter().cloned())
+        .filter(|a| !matches!(get_terms(&a.rhs)[..], [Term::Variable(_)]))
+        .collect();
+
+    // Replacements follow the syntax style of the source
+    // Each type is also rewritten with the best combination of the replacements found in it
+    let unicode = uses_unicode_syntax(source);
+    let mut matches: Vec<Match> = Vec::new();
+    let mut rewrites: Vec<Rewrite> = Vec::new();
+    for (node, context) in collect_types(&root) {
+        let occurrence = get_occurrence(&node, source_bytes, &fixities);
+        let bracketed = node.kind() == "type_parens";
+        let found: Vec<Match> = match_occurrence(
+            &occurrence,
+            context,
+            bracketed,
+            &aliases,
+            &kinds,
+            &extensions,
+        )
+        .into_iter()
+        .map(|m| Match {
+            replaced_type: render_syntax(&m.replaced_type, unicode),
+            ..flag_low_confidence(m, &parse_errors)
+        })
+        .collect();
+
+        let whole = Occurrence {
+            text: occurrence.text,
+            location: occurrence.location,
+            context,
+        };
+        rewrites.extend(rewrite_occurrence(&whole, &found));
+        matches.extend(found);
+    }
+
+    (matches, rewrites, parse_errors)
+}
+
+pub(crate) fn collect_types<'a>(node: &TSNode<'a>) -> Vec<(TSNode<'a>, Context)> {
+    // Finds the types written below `node` along with the syntax they were written in
+    let mut types = Vec::new();
+    let with = |context: Context| move |n: TSNode<'a>| (n, context);
+
+    match node.kind() {
+        "signature" => types.extend(annotated_type(node).map(with(signature_context(node)))),
+        "instance" => types.extend(
+            instance_types(node)
+                .into_iter()
+                .map(with(Context::InstanceHead)),
+        ),
+        "field" => types.extend(annotated_type(node).map(with(Context::RecordField))),
+        "gadt_constructor" => {
+            types.extend(annotated_type(node).map(with(Context::GadtConstructor)))
+        }
+        "data_constructor" | "data_constructor_infix" | "newtype_constructor" => {
+            let mut cursor = node.walk();
+            types.extend(
+                node.named_children(&mut cursor)
+                    .filter(|n| !NOT_FIELDS.contains(&n.kind()))
+                    .filter_map(|n| strip_strictness(&n))
+                    .map(with(Context::ConstructorField)),
+            );
+        }
+        // Visible type applications such as `@(Map Int Code)`
+        kind if kind.starts_with("exp") && has_child(node, "@") => {
+            types.extend(applied_type(node).map(with(Context::TypeApplication)))
+        }
+        // Annotations such as `(x :: Map Int Code)` in expressions and patterns
+        kind if kind.starts_with("exp") && has_child(node, "::") => {
+            types.extend(annotated_type(node).map(with(Context::ExpressionAnnotation)))
+        }
+        kind if kind.starts_with("pat") && has_child(node, "::") => {
+            types.extend(annotated_type(node).map(with(Context::PatternSignature)))
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        types.extend(collect_types(&child));
+    }
+
+    types
+}
+
+fn annotated_type<'a>(node: &TSNode<'a>) -> Option<TSNode<'a>> {
+    // The type of a signature, record field or GADT constructor is the named node after `::`
+    let mut cursor = node.walk();
+    let colons = node.children(&mut cursor).find(|n| n.kind() == "::")?;
+
+    let mut next = colons.next_named_sibling();
+    while let Some(n) = next {
+        if n.kind() != "comment" {
+            return strip_strictness(&n);
+        }
+        next = n.next_named_sibling();
+    }
+    None
+}
+
+fn applied_type<'a>(node: &TSNode<'a>) -> Option<TSNode<'a>> {
+    // The type of a visible type application is the named node after `@`
+    let mut cursor = node.walk();
+    let at = node.children(&mut cursor).find(|n| n.kind() == "@")?;
+    at.next_named_sibling()
+}
+
+fn instance_types<'a>(node: &TSNode<'a>) -> Vec<TSNode<'a>> {
+    // The types a class is applied to in the head of an instance
+    let mut cursor = node.walk();
+    let head = node
+        .children(&mut cursor)
+        .find(|n| n.kind() == "instance_head")
+        .unwrap_or(*node);
+
+    let mut cursor = head.walk();
+    let types = head
+        .named_children(&mut cursor)
+        .filter(|n| n.kind().starts_with("type"))
+        .collect();
+    types
+}
+
+fn has_child(node: &TSNode, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).any(|n| n.kind() == kind);
+    found
+}
+
+fn strip_strictness<'a>(node: &TSNode<'a>) -> Option<TSNode<'a>> {
+    // Strictness and laziness marks such as `!Int` aren't part of the field's type
+    if node.kind() == "strict_type" || node.kind() == "lazy_type" {
+        let mut cursor = node.walk();
+        let inner = node.named_children(&mut cursor).last();
+        return inner;
+    }
+    Some(*node)
+}
+
+fn signature_context(node: &TSNode) -> Context {
+    // Signatures belong to the module, a class or instance, or a `where` or `let` binding
+    let mut parent = node.parent();
+    while let Some(p) = parent {
+        if ["function", "bind", "exp_let", "exp_let_in"].contains(&p.kind()) {
+            return Context::LocalSignature;
+        }
+        if p.kind().starts_with("class") || p.kind().starts_with("instance") {
+            return Context::ClassMethod;
+        }
+        parent = p.parent();
+    }
+    Context::Signature
+}
+
+pub(crate) fn get_occurrence(
+    node: &TSNode,
+    source: &[u8],
+    fixities: &HashMap<String, Fixity>,
+) -> TypeTree {
+    // Parentheses left behind by a strictness mark such as `!(Map k v)` aren't needed
+    let tree = resolve_fixity(to_type_tree(node, source), fixities);
+    match &tree.children[..] {
+        [inner] if tree.kind == "type_parens" => inner.clone(),
+        _ => tree,
+    }
+}
+
+fn match_occurrence(
+    occurrence: &TypeTree,
+    context: Context,
+    bracketed: bool,
+    aliases: &[Alias],
+    kinds: &HashMap<String, String>,
+    extensions: &[String],
+) -> Vec<Match> {
+    // Matches aliases against a whole type and every compound type inside it
+    // The whole type can also be replaced by an alias of a single type name and have its
+    // context rewritten by a constraint synonym
+    // `bracketed` is whether the whole type was written in parentheses
+    let mut matches = Vec::new();
+
+    let target = target_from_tree(occurrence.clone());
+    let annotate = |alias: &Alias, m: Match, tree: &TypeTree, slot: Slot, depth: usize| {
+        // An alias applied to types needs parentheses where the type it replaces is applied to
+        // by something else, or it would be read as more arguments
+        let whole =
+            (tree.start_byte, tree.end_byte) == (occurrence.start_byte, occurrence.end_byte);
+        let argument = if whole {
+            !bracketed && APPLIED_CONTEXTS.contains(&context)
+        } else {
+            applied_arguments(&target.body).contains(&(tree.start_byte, tree.end_byte))
+        };
+        let replaced_type = if argument && m.replaced_type.split_whitespace().nth(1).is_some() {
+            format!("({})", m.replaced_type)
+        } else {
+            m.replaced_type.clone()
+        };
+
+        Match {
+            score: score_match(alias, &m, &tree.text, depth),
+            occurrence: Some(Occurrence {
+                text: tree.text.clone(),
+                location: tree.location.clone(),
+                context,
+            }),
+            legality: check_legality(
+                alias,
+                &m.variable_map,
+                aliases,
+                context,
+                slot,
+                tree,
+                extensions,
+            ),
+            replaced_type,
+            ..m
+        }
+    };
+
+    for alias in aliases.iter() {
+        let whole = match_alias(&target, alias, kinds)
+            .into_iter()
+            .chain(match_constraint_alias(&target, alias, kinds));
+        matches.extend(whole.map(|m| annotate(alias, m, occurrence, Slot::Result, 0)));
+    }
+
+    for (subtree, slot, depth) in subtrees(&target.body, Slot::Result, 0) {
+        if subtree.start_byte == target.body.start_byte && subtree.end_byte == target.body.end_byte
+        {
+            continue;
+        }
+
+        let target = target_from_tree(subtree.clone());
+        for alias in aliases.iter() {
+            if let Some(m) = match_alias(&target, alias, kinds) {
+                matches.push(annotate(alias, m, subtree, slot, depth));
+            }
+        }
+    }
+
+    matches
+}
+
+fn applied_arguments(tree: &TypeTree) -> Vec<(usize, usize)> {
+    // The byte ranges of the types a type constructor is applied to inside `tree`, leaving out
+    // those already in parentheses
+    let mut found = Vec::new();
+    if tree.kind == "type_apply" {
+        found.extend(
+            tree.children
+                .iter()
+                .skip(1)
+                .filter(|c| c.kind != "type_parens")
+                .map(|c| (c.start_byte, c.end_byte)),
+        );
+    }
+    for child in tree.children.iter() {
+        found.extend(applied_arguments(child));
+    }
+    found
+}
+
+pub(crate) fn subtrees(tree: &TypeTree, slot: Slot, depth: usize) -> Vec<(&TypeTree, Slot, usize)> {
+    // Lists the compound types inside `tree`, outermost first, with where each one appears and
+    // how many compound types it is inside of
+    // Parentheses are looked through and lone type names are left out
+    let mut found = Vec::new();
+
+    let compound =
+        tree.kind != "type_parens" && !tree.children.is_empty() && tree.kind != "type_name";
+    if compound {
+        found.push((tree, slot, depth));
+    }
+    let depth = if compound { depth + 1 } else { depth };
+    for (i, child) in tree.children.iter().enumerate() {
+        // Anything inside an argument of a type constructor stays an argument
+        let child_slot = match tree.kind.as_str() {
+            _ if slot == Slot::Argument => Slot::Argument,
+            "fun" if i == 0 => Slot::Domain,
+            "fun" | "type_parens" | "forall" | "context" => slot,
+            _ => Slot::Argument,
+        };
+        found.extend(subtrees(child, child_slot, depth));
+    }
+
+    found
+}
@@ -0,0 +1,57 @@
+// Contains the ranking model used to choose the best replacement when several aliases can
+// replace the same type
+
+use regex::Regex;
+
+use crate::types::{Alias, Match};
+
+// Weights of each part of a score. Saving tokens counts for the most, then using an alias with
+// fewer free parameters, replacing a type nearer the outside of its signature and using an
+// alias from the same module
+const TOKEN_WEIGHT: f64 = 1.0;
+const FREE_PARAM_WEIGHT: f64 = 1.5;
+const DEPTH_WEIGHT: f64 = 0.25;
+const LOCAL_WEIGHT: f64 = 0.5;
+
+pub(crate) fn score_match(alias: &Alias, m: &Match, replaced: &str, depth: usize) -> f64 {
+    // Scores `m`, which replaces the text `replaced` found `depth` compound types deep
+    // Parameters of the alias which are left as, or bound to, type variables are free
+    let saved = count_tokens(replaced) as f64 - count_tokens(&m.replaced_type) as f64;
+    let free = alias
+        .params
+        .iter()
+        .filter(|p| {
+            m.variable_map
+                .get(&p.name)
+                .is_none_or(|t| t.starts_with(|c: char| c.is_lowercase() || c == '_'))
+        })
+        .count() as f64;
+    let local = if m.origin.is_none() { 1.0 } else { 0.0 };
+
+    TOKEN_WEIGHT * saved - FREE_PARAM_WEIGHT * free - DEPTH_WEIGHT * depth as f64
+        + LOCAL_WEIGHT * local
+}
+
+fn count_tokens(text: &str) -> usize {
+    // Names, literals, arrows and punctuation each count as one token
+    let re_token = Regex::new(r#"[\w'.]+|"[^"]*"|->|=>|::|[^\s\w]"#).unwrap();
+    re_token.find_iter(text).count()
+}
+
+pub(crate) fn best_matches(matches: Vec<Match>) -> Vec<Match> {
+    // Keeps the highest scoring match for each location, or the first found if several have
+    // the same score. Matches without an occurrence all replace the target
+    let mut best: Vec<Match> = Vec::new();
+    for m in matches {
+        let location = m.occurrence.as_ref().map(|o| &o.location);
+        match best
+            .iter_mut()
+            .find(|b| b.occurrence.as_ref().map(|o| &o.location) == location)
+        {
+            Some(b) if m.score > b.score => *b = m,
+            Some(_) => {}
+            None => best.push(m),
+        }
+    }
+    best
+}
@@ -0,0 +1,206 @@
+// Contains the logic for suggesting new aliases for compound types which are written out
+// repeatedly across the modules of a project
+
+use std::collections::HashMap;
+
+use crate::alias::{
+    get_fixities, get_terms, haskell_parser, match_alias, normalise_syntax, normalise_whitespace,
+    substitute, target_from_tree,
+};
+use crate::cpp::preprocess;
+use crate::kind::get_kinds;
+use crate::legality::Slot;
+use crate::project::{read_module, ModuleKind};
+use crate::scan::{collect_types, get_occurrence, subtrees};
+use crate::types::{
+    AliasSuggestion, Occurrence, Range, RequestSuggest, ResponseSuggest, SuggestedOccurrence, Term,
+    TypeTree,
+};
+
+// A type must be written at least this many times before an alias is suggested for it
+const MIN_OCCURRENCES: usize = 2;
+
+// A type written out repeatedly, with the type variables of each place it was written
+struct Candidate {
+    text: String,
+    params: Vec<String>,
+    occurrences: Vec<(SuggestedOccurrence, Vec<String>)>,
+}
+
+pub fn suggest_aliases(request: RequestSuggest) -> ResponseSuggest {
+    // Public API function to suggest aliases for the compound types written out repeatedly in
+    // a set of files
+    // Details on RequestSuggest and ResponseSuggest can be found in types.rs
+    let modules: Vec<_> = request
+        .files
+        .iter()
+        .filter_map(|file| read_module(file, &request.default_extensions, &request.defines))
+        .filter(|m| m.kind == ModuleKind::Source)
+        .collect();
+
+    // Types which an existing alias already replaces are found by scanning instead
+    let aliases: Vec<_> = modules.iter().flat_map(|m| m.aliases.clone()).collect();
+    let mut kinds = HashMap::new();
+    for module in modules.iter() {
+        kinds.extend(get_kinds(&module.source));
+    }
+
+    // Initialise treesitter
+    let mut parser = haskell_parser();
+
+    // Types are grouped by their text with type variables renamed by where they are first used,
+    // so `Map k v` and `Map a b` are the same type
+    let mut candidates: HashMap<String, Candidate> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    for module in modules.iter() {
+        for variant in preprocess(
+            &module.source,
+            &request.default_extensions,
+            &request.defines,
+        ) {
+            let fixities = get_fixities(&variant);
+            let tree = parser.parse(normalise_syntax(&variant), None).unwrap();
+
+            for (node, context) in collect_types(&tree.root_node()) {
+                let whole = get_occurrence(&node, variant.as_bytes(), &fixities);
+                for (subtree, _, _) in subtrees(&whole, Slot::Result, 0) {
+                    if subtree.kind == "forall" || subtree.kind == "context" {
+                        continue;
+                    }
+                    let target = target_from_tree(subtree.clone());
+                    if aliases
+                        .iter()
+                        .any(|a| match_alias(&target, a, &kinds).is_some())
+                    {
+                        continue;
+                    }
+
+                    let params = type_variables(subtree);
+                    let renaming: HashMap<String, String> = params
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| (v.clone(), format!("v{}", i)))
+                        .collect();
+                    let key = normalise_whitespace(&substitute(subtree, &renaming));
+
+                    let found = SuggestedOccurrence {
+                        module: module.name.clone(),
+                        path: module.file.path.clone(),
+                        occurrence: Occurrence {
+                            text: subtree.text.clone(),
+                            location: subtree.location.clone(),
+                            context,
+                        },
+                    };
+                    let candidate = candidates.entry(key.clone()).or_insert_with(|| {
+                        order.push(key);
+                        Candidate {
+                            text: normalise_whitespace(&subtree.text),
+                            params: params.clone(),
+                            occurrences: Vec::new(),
+                        }
+                    });
+
+                    // Code shared by several versions of a source using CPP is only counted once
+                    if !candidate.occurrences.iter().any(|(o, _)| {
+                        o.path == found.path && o.occurrence.location == found.occurrence.location
+                    }) {
+                        candidate.occurrences.push((found, params));
+                    }
+                }
+            }
+        }
+    }
+
+    // Types saving the most are suggested first. A type only written inside the occurrences
+    // of a type already suggested, such as `(LockerState, String)` inside
+    // `Map Int (LockerState, String)`, isn't suggested as well
+    let mut ranked: Vec<(usize, &Candidate)> = order
+        .iter()
+        .map(|key| &candidates[key])
+        .filter(|c| c.occurrences.len() >= MIN_OCCURRENCES)
+        .filter_map(|c| Some((characters_saved(c, "NewAlias")?, c)))
+        .collect();
+    ranked.sort_by_key(|(saved, _)| std::cmp::Reverse(*saved));
+
+    let mut suggestions: Vec<AliasSuggestion> = Vec::new();
+    for (_, candidate) in ranked {
+        let nested = candidate.occurrences.iter().all(|(inner, _)| {
+            suggestions
+                .iter()
+                .flat_map(|s| s.occurrences.iter())
+                .any(|outer| contains(outer, inner))
+        });
+        if nested {
+            continue;
+        }
+
+        let name = format!("NewAlias{}", suggestions.len() + 1);
+        let characters_saved = match characters_saved(candidate, &name) {
+            Some(saved) => saved,
+            None => continue,
+        };
+        suggestions.push(AliasSuggestion {
+            declaration: declaration(candidate, &name),
+            name,
+            occurrences: candidate
+                .occurrences
+                .iter()
+                .map(|(o, _)| o.clone())
+                .collect(),
+            characters_saved,
+        });
+    }
+
+    ResponseSuggest {
+        echo_request: request,
+        suggestions,
+    }
+}
+
+fn type_variables(tree: &TypeTree) -> Vec<String> {
+    // The type variables of a type in the order they are first used, which become the
+    // parameters of its alias
+    let mut variables: Vec<String> = Vec::new();
+    for term in get_terms(tree) {
+        if let Term::Variable(v) = term {
+            if !variables.contains(&v) {
+                variables.push(v);
+            }
+        }
+    }
+    variables
+}
+
+fn declaration(candidate: &Candidate, name: &str) -> String {
+    let params: String = candidate.params.iter().map(|p| format!(" {}", p)).collect();
+    format!("type {}{} = {}", name, params, candidate.text)
+}
+
+fn characters_saved(candidate: &Candidate, name: &str) -> Option<usize> {
+    // Estimates the characters saved by writing the alias, applied to the type variables of
+    // each occurrence, in place of every occurrence, less those of its declaration
+    let written: usize = candidate
+        .occurrences
+        .iter()
+        .map(|(o, _)| o.occurrence.text.chars().count())
+        .sum();
+    let replaced: usize = candidate
+        .occurrences
+        .iter()
+        .map(|(_, params)| name.len() + params.iter().map(|p| p.len() + 1).sum::<usize>())
+        .sum();
+    let declared = declaration(candidate, name).chars().count() + 1;
+
+    written
+        .checked_sub(replaced + declared)
+        .filter(|saved| *saved > 0)
+}
+
+fn contains(outer: &SuggestedOccurrence, inner: &SuggestedOccurrence) -> bool {
+    // Whether `inner` was written inside of `outer`
+    let (o, i) = (&outer.occurrence.location, &inner.occurrence.location);
+    let start = |r: &Range| (r.start.row, r.start.col);
+    let end = |r: &Range| (r.end.row, r.end.col);
+    outer.path == inner.path && start(o) <= start(i) && end(i) <= end(o)
+}
@@ -5,57 +5,753 @@ use std::fmt;
 
 // REQUEST
 // Contains data needed for a request
+// Several targets can be matched against the source at once by listing them in `targets`,
+// either instead of or as well as `target_type`
+// With `best` only the highest scoring match of each target is kept
+// `explain` names an alias to explain the matching of against every target
+// With `near_miss`, aliases differing from a target by at most this distance are also reported
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RequestAlias {
+    #[serde(default)]
     pub target_type: String,
+    #[serde(default)]
+    pub targets: Vec<String>,
     pub source: String,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub literate: bool,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+    #[serde(default)]
+    pub best: bool,
+    #[serde(default)]
+    pub explain: Option<String>,
+    #[serde(default)]
+    pub near_miss: Option<usize>,
 }
 
 // RESPONSE
 // Contains information about matches found in response to a request
+// `matches` holds the matches of `target_type` and `results` the matches of every target
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResponseMatches {
     pub echo_request: RequestAlias,
     pub matches: Vec<Match>,
+    pub results: Vec<TargetMatches>,
+    pub parse_errors: Vec<ParseError>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TargetMatches {
+    pub target_type: String,
+    pub matches: Vec<Match>,
+    pub explanations: Vec<Explanation>,
+    pub near_misses: Vec<NearMiss>,
 }
 
 impl fmt::Display for ResponseMatches {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Human readable printing for a response
-        let matches: Vec<String> = self
-            .matches
-            .clone()
-            .into_iter()
-            .map(|m| m.replaced_type)
+        let results: Vec<String> = self
+            .results
+            .iter()
+            .map(|r| {
+                let matches: Vec<String> =
+                    r.matches.iter().map(|m| m.replaced_type.clone()).collect();
+                let explanations: String = r
+                    .explanations
+                    .iter()
+                    .map(|e| format!("\nExplained:\n\t{}", e))
+                    .collect();
+                let near_misses: String = r
+                    .near_misses
+                    .iter()
+                    .map(|n| format!("\nNear miss:\n\t{}", n))
+                    .collect();
+                format!(
+                    "Target type: {}\nMatched:\n\t{}{}{}",
+                    r.target_type,
+                    matches.join("\n\t"),
+                    explanations,
+                    near_misses
+                )
+            })
             .collect();
 
-        let target_type = self.echo_request.target_type.clone();
-
         let out_str = format!(
-            "Target type: {}\nMatched:\n\t{}",
-            target_type,
-            matches.join("\n\t")
+            "{}{}",
+            results.join("\n\n"),
+            format_parse_errors(&self.parse_errors)
         );
 
         write!(f, "{}", out_str)
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+// OPTIONS
+// Settings shared by the functions which run on files and projects
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub defines: Option<Vec<String>>,
+    pub best: bool,
+    pub explain: Option<String>,
+    pub near_miss: Option<usize>,
+}
+
+// TARGET ERROR
+// Returned instead of a response when the target type can't be parsed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TargetError {
+    pub target_type: String,
+    pub message: String,
+    pub column: usize,
+    pub hint: String,
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Points at the offending column of the target, which is counted from 0
+        write!(
+            f,
+            "Invalid target type: {}\n\t{}\n\t{}^\nHint: {}",
+            self.message,
+            self.target_type,
+            " ".repeat(self.column),
+            self.hint
+        )
+    }
+}
+
+// SCAN REQUEST
+// Asks for every type written in the source to be checked against the aliases in the source
+// With `best` only the highest scoring match of each occurrence is kept
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestScan {
+    pub source: String,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub literate: bool,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+    #[serde(default)]
+    pub best: bool,
+}
+
+// SCAN RESPONSE
+// Matches found while scanning, each with the occurrence of the type it replaces, and each
+// scanned type rewritten with the best combination of its matches
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseScan {
+    pub echo_request: RequestScan,
+    pub matches: Vec<Match>,
+    pub rewrites: Vec<Rewrite>,
+    pub parse_errors: Vec<ParseError>,
+}
+
+// A scanned type rewritten with the replacements which don't overlap and have the highest total
+// score
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    pub occurrence: Occurrence,
+    pub rewritten: String,
+    pub edits: Vec<Match>,
+    pub score: f64,
+}
+
+impl fmt::Display for ResponseScan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing for a scan, using 1-indexed lines
+        let matches: Vec<String> = self
+            .matches
+            .iter()
+            .filter_map(|m| {
+                let occurrence = m.occurrence.as_ref()?;
+                Some(format!(
+                    "Line {} ({:?}): {}\n\t\t{}",
+                    occurrence.location.start.row + 1,
+                    occurrence.context,
+                    occurrence.text,
+                    m.replaced_type
+                ))
+            })
+            .collect();
+
+        let rewrites: Vec<String> = self
+            .rewrites
+            .iter()
+            .map(|r| {
+                format!(
+                    "Line {}: {}\n\t\t{}",
+                    r.occurrence.location.start.row + 1,
+                    r.occurrence.text,
+                    r.rewritten
+                )
+            })
+            .collect();
+
+        write!(
+            f,
+            "Matched:\n\t{}\nRewritten:\n\t{}{}",
+            matches.join("\n\t"),
+            rewrites.join("\n\t"),
+            format_parse_errors(&self.parse_errors)
+        )
+    }
+}
+
+// APPLY REQUEST
+// A source with the matches chosen to be applied to it, usually found by scanning the source
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestApply {
+    pub source: String,
+    pub matches: Vec<Match>,
+    #[serde(default)]
+    pub literate: bool,
+}
+
+// APPLY RESPONSE
+// The rewritten source, the matches which were applied to it and those which couldn't be
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseApply {
+    pub echo_request: RequestApply,
+    pub source: String,
+    pub applied: Vec<Match>,
+    pub skipped: Vec<SkippedMatch>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SkippedMatch {
+    pub matched: Match,
+    pub reason: String,
+}
+
+impl fmt::Display for ResponseApply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing of the replacements made, using 1-indexed lines
+        let line = |m: &Match| {
+            m.occurrence
+                .as_ref()
+                .map_or(0, |o| o.location.start.row + 1)
+        };
+        let text = |m: &Match| {
+            m.occurrence
+                .as_ref()
+                .map_or("", |o| o.text.as_str())
+                .to_string()
+        };
+
+        let applied: Vec<String> = self
+            .applied
+            .iter()
+            .map(|m| format!("Line {}: {} -> {}", line(m), text(m), m.replaced_type))
+            .collect();
+        let skipped: Vec<String> = self
+            .skipped
+            .iter()
+            .map(|s| {
+                format!(
+                    "Line {}: {} -> {}\n\t\t{}",
+                    line(&s.matched),
+                    text(&s.matched),
+                    s.matched.replaced_type,
+                    s.reason
+                )
+            })
+            .collect();
+
+        write!(
+            f,
+            "Applied:\n\t{}\nSkipped:\n\t{}",
+            applied.join("\n\t"),
+            skipped.join("\n\t")
+        )
+    }
+}
+
+// PROJECT REQUEST
+// The files of a project, analysed together so each module can use the aliases of the modules
+// it imports. Without a target type every module is scanned
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestProject {
+    pub files: Vec<ProjectFile>,
+    #[serde(default)]
+    pub target_type: Option<String>,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+    #[serde(default)]
+    pub best: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProjectFile {
+    pub path: String,
+    pub source: String,
+}
+
+// PROJECT RESPONSE
+// Matches found in each module of a project
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseProject {
+    pub echo_request: RequestProject,
+    pub modules: Vec<ModuleMatches>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModuleMatches {
+    pub module: String,
+    pub path: String,
+    pub matches: Vec<Match>,
+    pub parse_errors: Vec<ParseError>,
+}
+
+impl fmt::Display for ResponseProject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing for a project, listing each module with matches
+        let modules: Vec<String> = self
+            .modules
+            .iter()
+            .filter(|m| !m.matches.is_empty())
+            .map(|m| {
+                let matches: Vec<String> = m
+                    .matches
+                    .iter()
+                    .map(|m| match &m.occurrence {
+                        Some(o) => format!(
+                            "Line {}: {} => {}",
+                            o.location.start.row + 1,
+                            o.text,
+                            m.replaced_type
+                        ),
+                        None => m.replaced_type.clone(),
+                    })
+                    .collect();
+                format!("{} ({}):\n\t{}", m.module, m.path, matches.join("\n\t"))
+            })
+            .collect();
+
+        write!(f, "{}", modules.join("\n"))
+    }
+}
+
+// CATALOG REQUEST
+// The files whose aliases are compared with each other. A single file can be catalogued alone
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestCatalog {
+    pub files: Vec<ProjectFile>,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+}
+
+// CATALOG RESPONSE
+// Groups of aliases which are equal up to renaming their type variables, and aliases which are
+// instances of more general aliases, such as `type TestFunc = Int -> Int` of
+// `type GFunctionBin a = a -> a`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseCatalog {
+    pub echo_request: RequestCatalog,
+    pub equivalent: Vec<Vec<CatalogEntry>>,
+    pub specialisations: Vec<Specialisation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    pub alias: String,
+    pub declaration: String,
+    pub location: Range,
+    pub module: String,
+    pub path: String,
+}
+
+// `specific` is the same type as `instance`, which applies the `general` alias
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Specialisation {
+    pub specific: CatalogEntry,
+    pub general: CatalogEntry,
+    pub instance: String,
+}
+
+impl fmt::Display for CatalogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing of where an alias is declared, using 1-indexed lines
+        write!(
+            f,
+            "{} ({} line {})",
+            self.alias,
+            self.path,
+            self.location.start.row + 1
+        )
+    }
+}
+
+impl fmt::Display for ResponseCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing for a catalog, listing each group of equivalent aliases and
+        // each alias which is an instance of another
+        let equivalent: Vec<String> = self
+            .equivalent
+            .iter()
+            .map(|g| {
+                let entries: Vec<String> = g.iter().map(|e| e.to_string()).collect();
+                entries.join(", ")
+            })
+            .collect();
+        let specialisations: Vec<String> = self
+            .specialisations
+            .iter()
+            .map(|s| format!("{} is {}, using {}", s.specific, s.instance, s.general))
+            .collect();
+
+        write!(
+            f,
+            "Equivalent aliases:\n\t{}\nMore specific aliases:\n\t{}",
+            equivalent.join("\n\t"),
+            specialisations.join("\n\t")
+        )
+    }
+}
+
+// UNUSED REQUEST
+// The files of a project to find the aliases which are never referenced in
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestUnused {
+    pub files: Vec<ProjectFile>,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+}
+
+// UNUSED RESPONSE
+// Aliases which no signature, annotation, other alias, data field or export list refers to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseUnused {
+    pub echo_request: RequestUnused,
+    pub unused: Vec<UnusedAlias>,
+}
+
+// `exported_by` lists the modules which still export the alias without naming it, so removing
+// it could break code outside of the project
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnusedAlias {
+    pub alias: CatalogEntry,
+    pub exported_by: Vec<String>,
+}
+
+impl fmt::Display for ResponseUnused {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing for the unused aliases of a project
+        let unused: Vec<String> = self
+            .unused
+            .iter()
+            .map(|u| match &u.exported_by[..] {
+                [] => u.alias.to_string(),
+                modules => format!("{}, exported by {}", u.alias, modules.join(", ")),
+            })
+            .collect();
+
+        write!(f, "Unused aliases:\n\t{}", unused.join("\n\t"))
+    }
+}
+
+// SUGGEST REQUEST
+// The files to look for compound types written out repeatedly in
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RequestSuggest {
+    pub files: Vec<ProjectFile>,
+    #[serde(default)]
+    pub default_extensions: Vec<String>,
+    #[serde(default)]
+    pub defines: Option<Vec<String>>,
+}
+
+// SUGGEST RESPONSE
+// New aliases which could replace types written out repeatedly, saving the most characters first
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResponseSuggest {
+    pub echo_request: RequestSuggest,
+    pub suggestions: Vec<AliasSuggestion>,
+}
+
+// `name` is a placeholder to be replaced with a meaningful name. `characters_saved` estimates
+// the characters saved by using the alias everywhere, less those of its declaration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AliasSuggestion {
+    pub name: String,
+    pub declaration: String,
+    pub occurrences: Vec<SuggestedOccurrence>,
+    pub characters_saved: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SuggestedOccurrence {
+    pub module: String,
+    pub path: String,
+    pub occurrence: Occurrence,
+}
+
+impl fmt::Display for ResponseSuggest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing for suggested aliases, using 1-indexed lines
+        let suggestions: Vec<String> = self
+            .suggestions
+            .iter()
+            .map(|s| {
+                let occurrences: Vec<String> = s
+                    .occurrences
+                    .iter()
+                    .map(|o| format!("{} line {}", o.path, o.occurrence.location.start.row + 1))
+                    .collect();
+                format!(
+                    "{}\n\tSaves {} characters, replacing:\n\t\t{}",
+                    s.declaration,
+                    s.characters_saved,
+                    occurrences.join("\n\t\t")
+                )
+            })
+            .collect();
+
+        write!(f, "Suggested aliases:\n{}", suggestions.join("\n"))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Match {
     pub matched: String,
     pub location: Range,
     pub variable_map: HashMap<String, String>,
     pub replaced_type: String,
+    pub occurrence: Option<Occurrence>,
+    pub legality: Legality,
+    pub origin: Option<Origin>,
+    pub low_confidence: bool,
+    pub score: f64,
+}
+
+// EXPLANATION
+// Whether an alias named in a request matched a target, and if not the step where matching
+// failed. There is an explanation for every alias with the name, including imported ones
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub alias: String,
+    pub declaration: Option<String>,
+    pub location: Option<Range>,
+    pub origin: Option<Origin>,
+    pub replaced_type: Option<String>,
+    pub failure: Option<Failure>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing of an explanation, using 1-indexed lines
+        let line = match &self.location {
+            Some(l) => format!(" (line {})", l.start.row + 1),
+            None => String::new(),
+        };
+        match (&self.replaced_type, &self.failure) {
+            (_, Some(failure)) => write!(f, "{}{}: {}", self.alias, line, failure),
+            (Some(replaced), None) => write!(f, "{}{}: matched as {}", self.alias, line, replaced),
+            (None, None) => write!(f, "{}{}: matched", self.alias, line),
+        }
+    }
+}
+
+// The step at which an alias failed to match a target, with the parts of each involved
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "step")]
+pub enum Failure {
+    NotInScope,
+    StructureMismatch {
+        alias: String,
+        target: String,
+    },
+    ConcreteMismatch {
+        alias: String,
+        target: String,
+    },
+    InconsistentBinding {
+        variable: String,
+        first: String,
+        second: String,
+    },
+    KindClash {
+        param: String,
+        bound: String,
+        declared: String,
+        inferred: String,
+    },
+    ScopeProblem {
+        variable: String,
+        reason: String,
+    },
+    MissingConstraint {
+        constraint: String,
+    },
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::NotInScope => write!(f, "no alias with this name is in scope"),
+            Failure::StructureMismatch { alias, target } => write!(
+                f,
+                "structure mismatch between `{}` in the alias and `{}` in the target",
+                alias, target
+            ),
+            Failure::ConcreteMismatch { alias, target } => write!(
+                f,
+                "concrete type mismatch, `{}` in the alias against `{}` in the target",
+                alias, target
+            ),
+            Failure::InconsistentBinding {
+                variable,
+                first,
+                second,
+            } => write!(
+                f,
+                "inconsistent binding, `{}` is bound to both `{}` and `{}`",
+                variable, first, second
+            ),
+            Failure::KindClash {
+                param,
+                bound,
+                declared,
+                inferred,
+            } => write!(
+                f,
+                "kind clash, `{}` has kind `{}` but is bound to `{}` of kind `{}`",
+                param, declared, bound, inferred
+            ),
+            Failure::ScopeProblem { variable, reason } => {
+                write!(f, "scope problem, `{}` {}", variable, reason)
+            }
+            Failure::MissingConstraint { constraint } => write!(
+                f,
+                "the constraint `{}` isn't in the target's context",
+                constraint
+            ),
+        }
+    }
+}
+
+// NEAR MISS
+// An alias which doesn't replace a target but differs from it in only a few positions
+// `distance` counts the terms covered by the differences
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NearMiss {
+    pub alias: String,
+    pub declaration: String,
+    pub location: Range,
+    pub origin: Option<Origin>,
+    pub distance: usize,
+    pub differences: Vec<Difference>,
+}
+
+// A part of an alias and the part of the target in its position, which is at `column` of the
+// target counting from 0
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub alias: String,
+    pub target: String,
+    pub column: usize,
+}
+
+impl fmt::Display for NearMiss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Human readable printing of a near miss, using 1-indexed lines
+        let differences: Vec<String> = self
+            .differences
+            .iter()
+            .map(|d| {
+                format!(
+                    "`{}` in the alias against `{}` at column {}",
+                    d.alias, d.target, d.column
+                )
+            })
+            .collect();
+        write!(
+            f,
+            "{} (line {}), distance {}: {}",
+            self.alias,
+            self.location.start.row + 1,
+            self.distance,
+            differences.join(", ")
+        )
+    }
+}
+
+// Syntax the Haskell grammar couldn't parse. Matches overlapping it have low confidence
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub location: Range,
+}
+
+fn format_parse_errors(errors: &[ParseError]) -> String {
+    // Human readable parse errors, using 1-indexed lines
+    if errors.is_empty() {
+        return String::new();
+    }
+
+    let errors: Vec<String> = errors
+        .iter()
+        .map(|e| format!("Line {}: {}", e.location.start.row + 1, e.message))
+        .collect();
+    format!("\nParse errors:\n\t{}", errors.join("\n\t"))
+}
+
+// The module an alias was imported from when analysing a project
+// Aliases read from a boot file or Backpack signature are linked to the module implementing it
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Origin {
+    pub module: String,
+    pub path: String,
+    pub implemented_in: Option<String>,
+}
+
+// Whether GHC accepts a replacement with the extensions enabled for the module
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum Legality {
+    Legal,
+    NeedsExtensions(Vec<String>),
+    Illegal(String),
+}
+
+// Where a type that can be replaced was found when scanning a source file
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub text: String,
+    pub location: Range,
+    pub context: Context,
+}
+
+// The kind of syntax a scanned type was written in
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum Context {
+    Signature,
+    ClassMethod,
+    LocalSignature,
+    RecordField,
+    ConstructorField,
+    GadtConstructor,
+    ExpressionAnnotation,
+    PatternSignature,
+    TypeApplication,
+    InstanceHead,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Range {
     pub start: Position,
     pub end: Position,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Position {
     pub row: usize,
     pub col: usize,
@@ -65,13 +761,86 @@ pub struct Position {
 // Used to convert a type signature to a list of terms
 #[derive(Debug)]
 pub struct Target {
-    pub query_str: String,
+    pub shape: String,
     pub source: String,
     pub terms: Vec<Term>,
+    pub quantified: Vec<String>,
+    pub constraints: Vec<TypeTree>,
+    pub body: TypeTree,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Term {
     Type(String),
     Variable(String),
+    Literal(String),
+}
+
+// ALIAS
+// A type alias found in the source with its right hand side split into a quantifier list, a
+// constraint context and the type being aliased
+#[derive(Debug, Clone)]
+pub struct Alias {
+    pub declaration: String,
+    pub origin: Option<Origin>,
+    pub name: String,
+    pub params: Vec<Param>,
+    pub infix: bool,
+    pub location: Range,
+    pub quantified: Vec<String>,
+    pub constraints: Vec<TypeTree>,
+    pub rhs: TypeTree,
+}
+
+// A parameter of an alias with its declared kind, if any
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub kind: Option<String>,
+}
+
+// TYPE FAMILY
+// A type family declared in the source and the equations known for it. Equations of a closed
+// family are tried in order. Defaults of associated types are indexed but never evaluated as
+// an instance may override them
+#[derive(Debug, Clone)]
+pub struct TypeFamily {
+    pub name: String,
+    pub closed: bool,
+    pub equations: Vec<Equation>,
+    pub defaults: Vec<Equation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Equation {
+    pub args: Vec<TypeTree>,
+    pub rhs: TypeTree,
+}
+
+// FIXITY
+// Declared precedence and associativity of an infix type operator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fixity {
+    pub associativity: Associativity,
+    pub precedence: u8,
+}
+
+// TYPE TREE
+// An owned copy of a Treesitter subtree containing only named nodes. Byte offsets are kept so
+// text can be substituted back into the original source
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeTree {
+    pub kind: String,
+    pub text: String,
+    pub children: Vec<TypeTree>,
+    pub location: Range,
+    pub start_byte: usize,
+    pub end_byte: usize,
 }
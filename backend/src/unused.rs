@@ -0,0 +1,164 @@
+// Contains the logic for finding the aliases of a project which are never referenced, either by
+// the types written in its modules, by other aliases or by an export list
+
+use regex::Regex;
+
+use crate::alias::{haskell_parser, normalise_syntax, strip_comments};
+use crate::cpp::preprocess;
+use crate::project::{read_module, Module, ModuleKind};
+use crate::scan::collect_types;
+use crate::types::{CatalogEntry, RequestUnused, ResponseUnused, UnusedAlias};
+
+// The names listed in the export list of a module and the modules it re-exports
+struct Exports {
+    names: Vec<String>,
+    modules: Vec<String>,
+}
+
+pub fn unused_aliases(request: RequestUnused) -> ResponseUnused {
+    // Public API function to find the aliases of a project which are never referenced
+    // Details on RequestUnused and ResponseUnused can be found in types.rs
+    // References in any file count, but only aliases declared in source modules are reported
+    let modules: Vec<Module> = request
+        .files
+        .iter()
+        .filter_map(|file| read_module(file, &request.default_extensions, &request.defines))
+        .collect();
+    let references: Vec<Vec<String>> = modules
+        .iter()
+        .map(|m| referenced_names(m, &request))
+        .collect();
+    let exports: Vec<Option<Exports>> = modules.iter().map(|m| read_exports(&m.source)).collect();
+
+    let mut unused = Vec::new();
+    for (d, declaring) in modules.iter().enumerate() {
+        if declaring.kind != ModuleKind::Source {
+            continue;
+        }
+
+        // Names are referenced in the module declaring them and the modules importing it
+        let in_scope = |i: usize| i == d || modules[i].imports_module(&declaring.name);
+
+        for alias in declaring.aliases.iter() {
+            let referenced = (0..modules.len()).any(|i| {
+                in_scope(i)
+                    && (references[i].contains(&alias.name)
+                        || exports[i]
+                            .as_ref()
+                            .is_some_and(|e| e.names.contains(&alias.name)))
+            });
+            if referenced {
+                continue;
+            }
+
+            // A module without an export list exports everything it declares, and a module can
+            // re-export the whole of a module in scope
+            let exported_by = (0..modules.len())
+                .filter(|i| match &exports[*i] {
+                    None => *i == d,
+                    Some(e) => in_scope(*i) && e.modules.contains(&declaring.name),
+                })
+                .map(|i| modules[i].name.clone())
+                .collect();
+
+            unused.push(UnusedAlias {
+                alias: CatalogEntry {
+                    alias: alias.name.clone(),
+                    declaration: alias.declaration.clone(),
+                    location: alias.location.clone(),
+                    module: declaring.name.clone(),
+                    path: declaring.file.path.clone(),
+                },
+                exported_by,
+            });
+        }
+    }
+
+    ResponseUnused {
+        echo_request: request,
+        unused,
+    }
+}
+
+fn referenced_names(module: &Module, request: &RequestUnused) -> Vec<String> {
+    // Finds the names used in the types written in a module and in the right hand sides of its
+    // aliases, in any branch of its CPP conditionals
+    // Initialise treesitter
+    let mut parser = haskell_parser();
+
+    let mut texts: Vec<String> = module
+        .aliases
+        .iter()
+        .filter_map(|a| Some(a.declaration.split_once('=')?.1.to_string()))
+        .collect();
+    for variant in preprocess(
+        &module.source,
+        &request.default_extensions,
+        &request.defines,
+    ) {
+        let tree = parser.parse(normalise_syntax(&variant), None).unwrap();
+        for (node, _) in collect_types(&tree.root_node()) {
+            texts.extend(node.utf8_text(variant.as_bytes()).ok().map(String::from));
+        }
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for text in texts.iter() {
+        for name in names_in(&strip_comments(text)) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+fn names_in(text: &str) -> Vec<String> {
+    // Splits text into the names and operators it uses, without their qualifiers
+    let re_name = Regex::new(r"[A-Za-z_][\w'.]*|[!#$%&*+./<=>?@\\^|~:-]+").unwrap();
+
+    re_name
+        .find_iter(text)
+        .map(|m| {
+            let name = m.as_str();
+            match name.rsplit_once('.') {
+                Some((_, unqualified)) if name.starts_with(char::is_alphabetic) => {
+                    unqualified.to_string()
+                }
+                _ => name.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn read_exports(source: &str) -> Option<Exports> {
+    // Reads the export list of a module such as `module Types (Table, module Types.Extra) where`
+    // Modules without an export list, or without a header, have no exports listed
+    let re_header = Regex::new(r"(?m)^(module|signature)\s+[A-Z][\w.]*\s*").unwrap();
+    let after = strip_comments(&source[re_header.find(source)?.end()..]);
+    let after = after.trim_start();
+    if !after.starts_with('(') {
+        return None;
+    }
+
+    // The export list ends at the bracket closing the first one
+    let mut depth = 0;
+    let end = after.char_indices().find_map(|(i, c)| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(i)
+    })?;
+    let list = &after[1..end];
+
+    let re_module = Regex::new(r"\bmodule\s+([A-Z][\w.]*)").unwrap();
+    Some(Exports {
+        names: names_in(&re_module.replace_all(list, " ")),
+        modules: re_module
+            .captures_iter(list)
+            .map(|c| c[1].to_string())
+            .collect(),
+    })
+}
@@ -0,0 +1,130 @@
+// Tests rewriting a source with the replacements found by scanning it
+
+use htar::{apply_matches, fix_file, scan_file, unified_diff, Options, RequestApply};
+
+const REWRITTEN: &str = "module Apply where
+
+import Data.Map (Map)
+
+type Table = Map Int String
+type Listing a = [a]
+
+-- | Looks up a name
+lookupName :: Table -> Int -> Maybe String
+lookupName table key = undefined
+
+names :: Table {- the table -} -> Listing String
+names = undefined
+
+both :: (Table, Table) -> Int
+both = undefined
+
+data a --> b = Arrow
+
+type Handler = Int --> String
+
+handle :: Handler
+handle = undefined
+
+counts :: Maybe (Listing Int) -> Int
+counts = undefined
+";
+
+#[test]
+fn fix() {
+    let applied = fix_file("tests/input_files/apply.hs".into(), &Options::default());
+    assert_eq!(applied.source, REWRITTEN);
+    assert_eq!(applied.applied.len(), 7);
+    assert!(applied.skipped.is_empty());
+
+    // A replacement for a type which is applied to is written in parentheses
+    let diff = unified_diff(&applied.echo_request.source, &applied.source, "apply.hs");
+    assert!(
+        diff.contains("\n-counts :: Maybe [Int] -> Int\n+counts :: Maybe (Listing Int) -> Int\n")
+    );
+}
+
+#[test]
+fn fix_with_rewrites() {
+    // Each type is fixed with the replacements chosen for its rewrite, and replacements
+    // overlapping them are skipped
+    let path = "tests/input_files/rewrite.hs";
+    let scan = scan_file(path.into(), &Options::default());
+    let chosen: Vec<_> = scan.rewrites.into_iter().flat_map(|r| r.edits).collect();
+    let applied = fix_file(path.into(), &Options::default());
+    assert_eq!(applied.applied, chosen);
+    let skipped: Vec<&str> = applied
+        .skipped
+        .iter()
+        .map(|s| s.matched.replaced_type.as_str())
+        .collect();
+    assert_eq!(skipped, vec!["Locker"]);
+}
+
+#[test]
+fn overlapping_matches() {
+    let scan = scan_file("tests/input_files/apply.hs".into(), &Options::default());
+    let first = scan.matches[0].clone();
+
+    // A match selected twice is applied once
+    let applied = apply_matches(RequestApply {
+        source: scan.echo_request.source.clone(),
+        matches: vec![first.clone(), first.clone()],
+        literate: false,
+    });
+    assert_eq!(applied.applied.len(), 1);
+    assert!(applied.skipped.is_empty());
+
+    // A different replacement for an overlapping range is skipped
+    let mut overlapping = first.clone();
+    overlapping.replaced_type = "Other".to_string();
+    let applied = apply_matches(RequestApply {
+        source: scan.echo_request.source.clone(),
+        matches: vec![first.clone(), overlapping],
+        literate: false,
+    });
+    assert_eq!(applied.applied, vec![first.clone()]);
+    assert_eq!(applied.skipped.len(), 1);
+
+    // Matches for a different version of the source are skipped
+    let applied = apply_matches(RequestApply {
+        source: "module Apply where\n".to_string(),
+        matches: vec![first],
+        literate: false,
+    });
+    assert_eq!(applied.source, "module Apply where\n");
+    assert_eq!(applied.skipped.len(), 1);
+}
+
+#[test]
+fn diff() {
+    let original = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+    let rewritten = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nK\nl\n";
+    let expected = "--- a/file.hs
++++ b/file.hs
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -8,4 +8,5 @@
+ h
+ i
+ j
+-k
++K
++l
+";
+    assert_eq!(unified_diff(original, rewritten, "file.hs"), expected);
+    assert_eq!(unified_diff(original, original, "file.hs"), "");
+
+    let expected = "--- a/file.hs
++++ b/file.hs
+@@ -0,0 +1,2 @@
++x
++y
+";
+    assert_eq!(unified_diff("", "x\ny\n", "file.hs"), expected);
+}
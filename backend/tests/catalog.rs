@@ -0,0 +1,58 @@
+// Tests finding aliases which are equivalent to each other or instances of other aliases
+
+use htar::{catalog_path, Options, ResponseCatalog};
+
+fn catalog() -> ResponseCatalog {
+    catalog_path("tests/input_files/catalog".into(), &Options::default())
+}
+
+#[test]
+fn equivalent_aliases() {
+    // Aliases are equivalent across modules and after renaming their variables
+    let groups: Vec<Vec<String>> = catalog()
+        .equivalent
+        .iter()
+        .map(|g| {
+            g.iter()
+                .map(|e| format!("{}.{}", e.module, e.alias))
+                .collect()
+        })
+        .collect();
+
+    assert!(groups.contains(&vec![
+        "Types.TestAlias".to_string(),
+        "Types.TestAliasDupe".to_string()
+    ]));
+    assert!(groups.contains(&vec![
+        "Handlers.Endo".to_string(),
+        "Types.GFunctionBin".to_string()
+    ]));
+    assert!(groups.contains(&vec![
+        "Handlers.Couple".to_string(),
+        "Types.Pair".to_string()
+    ]));
+
+    // Swapping parameters gives a different alias
+    assert!(groups.iter().flatten().all(|a| a != "Types.Swapped"));
+}
+
+#[test]
+fn more_specific_aliases() {
+    let specialisations: Vec<(String, String)> = catalog()
+        .specialisations
+        .iter()
+        .map(|s| (s.specific.alias.clone(), s.instance.clone()))
+        .collect();
+
+    assert!(specialisations.contains(&(
+        "TestFunc".to_string(),
+        "GFunctionBin Int".to_string()
+    )));
+    assert!(specialisations.contains(&("Pair".to_string(), "Twin a a".to_string())));
+
+    // Aliases which only rename or reorder the parameters of another aren't more specific
+    assert!(specialisations
+        .iter()
+        .all(|(a, _)| a != "Twin" && a != "Swapped"));
+    assert!(specialisations.iter().all(|(_, i)| !i.starts_with("Pair")));
+}
@@ -1,14 +1,21 @@
-use htar::run_on_file;
+use htar::{run_on_file, run_on_project, scan_file, Match, Options};
 
+#[allow(dead_code)]
 pub struct TestCase<'a> {
     pub path: &'a str,
     pub target: &'a str,
     pub result: Vec<&'a str>,
 }
 
+#[allow(dead_code)]
 pub fn test_on_file(case: TestCase) {
+    test_with_options(case, &Options::default());
+}
+
+#[allow(dead_code)]
+pub fn test_with_options(case: TestCase, options: &Options) {
     let full_path = format!("tests/input_files/{}", case.path);
-    let response = run_on_file(full_path.into(), case.target.into());
+    let response = run_on_file(full_path.into(), case.target.into(), options).unwrap();
     let mut replaced_types: Vec<String> = response
         .matches
         .into_iter()
@@ -23,3 +30,81 @@ pub fn test_on_file(case: TestCase) {
         format!("{:?} != {:?}", replaced_types, case.result)
     );
 }
+
+// Only used by the tests which scan files
+#[allow(dead_code)]
+pub struct ScanCase<'a> {
+    pub path: &'a str,
+    pub result: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+#[allow(dead_code)]
+pub fn test_scan_on_file(case: ScanCase) {
+    // Compares each scanned type with its replacement and the syntax it was written in
+    test_scan_with(case, |m| {
+        let occurrence = m.occurrence.clone().unwrap();
+        (
+            occurrence.text,
+            m.replaced_type.clone(),
+            format!("{:?}", occurrence.context),
+        )
+    });
+}
+
+#[allow(dead_code)]
+pub fn test_scan_with(case: ScanCase, projection: impl Fn(&Match) -> (String, String, String)) {
+    // Compares the part of each scanned match picked out by `projection`
+    let full_path = format!("tests/input_files/{}", case.path);
+    let response = scan_file(full_path.into(), &Options::default());
+    let found = response.matches.iter().map(projection).collect();
+
+    assert_results(found, &case.result);
+}
+
+#[allow(dead_code)]
+pub fn test_project(case: ScanCase, target: Option<&str>) {
+    // Compares the module, replacement and origin of every match in a project
+    // Origins are written as the file declaring the alias and the file implementing it
+    let full_path = format!("tests/input_files/{}", case.path);
+    let response = run_on_project(
+        full_path.into(),
+        target.map(|t| t.to_string()),
+        &Options::default(),
+    )
+    .unwrap();
+    let file_name = |path: &str| path.rsplit('/').next().unwrap().to_string();
+
+    let found = response
+        .modules
+        .into_iter()
+        .flat_map(|module| {
+            module.matches.into_iter().map(move |m| {
+                let origin = match m.origin {
+                    None => String::new(),
+                    Some(o) => match o.implemented_in {
+                        None => file_name(&o.path),
+                        Some(i) => format!("{} -> {}", file_name(&o.path), file_name(&i)),
+                    },
+                };
+                (module.module.clone(), m.replaced_type, origin)
+            })
+        })
+        .collect();
+
+    assert_results(found, &case.result);
+}
+
+fn assert_results(mut found: Vec<(String, String, String)>, result: &[(&str, &str, &str)]) {
+    found.sort();
+
+    let expected: Vec<(String, String, String)> = result
+        .iter()
+        .map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string()))
+        .collect();
+
+    assert!(
+        found == expected,
+        "{}",
+        format!("{:?} != {:?}", found, expected)
+    );
+}
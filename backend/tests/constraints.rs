@@ -0,0 +1,62 @@
+// Tests matching constraint contexts of targets against constraint synonyms
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn constraint_synonym() {
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(MonadLogger m, MonadIO m, MonadReader Env m) => Int -> m ()",
+        result: vec!["MonadApp m => Int -> m ()"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(MonadIO m, Show a, MonadReader Env m, MonadLogger m) => a -> m ()",
+        result: vec!["(MonadApp m, Show a) => a -> m ()"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "PrettyShow b => b -> String",
+        result: vec!["Pretty b => b -> String"],
+    };
+    test_on_file(test_case);
+
+    // Only the first consistent assignment of constraints is reported
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(Monad m, Show b, Show a) => a -> m b",
+        result: vec!["(MonadShow m b, Show a) => a -> m b"],
+    };
+    test_on_file(test_case);
+
+    // Classes which aren't declared in the source are constraints given a kind signature
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(Show a, Render a) => a -> Text",
+        result: vec!["Renderable a => a -> Text"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn constraint_synonym_fail() {
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(MonadIO m, MonadReader Config m, MonadLogger m) => m ()",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    // Type constructors aren't classes
+    let test_case = TestCase {
+        path: "constraints.hs",
+        target: "(Maybe a, Show a) => a -> String",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
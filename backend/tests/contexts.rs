@@ -0,0 +1,76 @@
+// Tests matching targets and aliases with constraint contexts and quantifiers
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn context_target() {
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "(Ord k, Show v) => Map k v -> String",
+        result: vec!["(Ord k, Show v) => MapFn k v"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "Map Int Char -> String",
+        result: vec!["MapFn Int Char"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn context_alias() {
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "Show a => a -> String",
+        result: vec!["ShowFn a"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "(Show a, Eq a) => a -> String",
+        result: vec!["Eq a => ShowFn a"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "a -> String",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn forall_alias() {
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "Functor f => (Int -> f Int) -> Person -> f Person",
+        result: vec!["Lens Person Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "forall f. (Functor f, Contravariant f) => (Int -> f Int) -> Person -> f Person",
+        result: vec!["Getter Person Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "(Int -> f Int) -> Person -> f Person",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "(Functor f, Show (f Int)) => (Int -> f Int) -> Person -> f Person",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
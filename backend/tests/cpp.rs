@@ -0,0 +1,97 @@
+// Tests files using CPP, both with defines and by analysing every branch
+
+mod common;
+use common::{test_on_file, test_scan_on_file, test_with_options, ScanCase, TestCase};
+use htar::{scan_file, Options};
+
+#[test]
+fn every_branch() {
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "Int -> IO ()",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "Int -> IO Bool",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "String -> IO ()",
+        result: vec!["Handler String", "Logger"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn nested_branches() {
+    let test_case = TestCase {
+        path: "cpp_nested.hs",
+        target: "Int -> Bool",
+        result: vec!["Result"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "cpp_nested.hs",
+        target: "Int -> IO Bool",
+        result: vec!["Result"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "cpp_nested.hs",
+        target: "Int -> Maybe Bool",
+        result: vec!["Result"],
+    };
+    test_on_file(test_case);
+}
+
+fn defines(defines: &[&str]) -> Options {
+    Options {
+        defines: Some(defines.iter().map(|d| d.to_string()).collect()),
+        ..Options::default()
+    }
+}
+
+#[test]
+fn with_defines() {
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "Int -> IO ()",
+        result: vec![],
+    };
+    test_with_options(test_case, &defines(&["DEBUG"]));
+
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "String -> IO ()",
+        result: vec!["Logger"],
+    };
+    test_with_options(test_case, &defines(&["DEBUG"]));
+
+    let test_case = TestCase {
+        path: "cpp.hs",
+        target: "String -> IO ()",
+        result: vec!["Handler String"],
+    };
+    test_with_options(test_case, &defines(&["MIN_VERSION_base=1"]));
+}
+
+#[test]
+fn shared_code_reported_once() {
+    let test_case = ScanCase {
+        path: "cpp.hs",
+        result: vec![("Map Int String", "Table", "Signature")],
+    };
+    test_scan_on_file(test_case);
+
+    let response = scan_file("tests/input_files/cpp.hs".into(), &Options::default());
+    let location = response.matches[0].occurrence.clone().unwrap().location;
+    assert_eq!((location.start.row, location.start.col), (17, 13));
+}
@@ -0,0 +1,92 @@
+// Tests files containing syntax errors, which are matched on the parts which parsed, and target
+// types which can't be parsed
+
+mod common;
+use common::{test_on_file, TestCase};
+use htar::{run_on_file, run_on_project, scan_file, Options};
+
+#[test]
+fn matches_around_errors() {
+    let test_case = TestCase {
+        path: "errors.hs",
+        target: "Map Int String",
+        result: vec!["Table"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "errors.hs",
+        target: "Map Int String -> [String]",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn reports_errors() {
+    let response = run_on_file(
+        "tests/input_files/errors.hs".into(),
+        "Map Int String".into(),
+        &Options::default(),
+    )
+    .unwrap();
+    assert!(!response.parse_errors.is_empty());
+    assert!(response
+        .parse_errors
+        .iter()
+        .all(|e| e.location.start.row == 10));
+    assert!(response.matches.iter().all(|m| !m.low_confidence));
+
+    let response = scan_file("tests/input_files/errors.hs".into(), &Options::default());
+    assert!(!response.parse_errors.is_empty());
+}
+
+#[test]
+fn flags_overlapping_matches() {
+    // Only types written on the line with the error have low confidence
+    let response = scan_file("tests/input_files/errors.hs".into(), &Options::default());
+    let rows: Vec<(usize, bool)> = response
+        .matches
+        .iter()
+        .map(|m| {
+            let occurrence = m.occurrence.clone().unwrap();
+            (occurrence.location.start.row, m.low_confidence)
+        })
+        .collect();
+
+    assert!(rows.contains(&(7, false)));
+    assert!(rows.contains(&(13, false)));
+    assert!(rows.iter().all(|(row, low)| *low == (*row == 10)));
+}
+
+#[test]
+fn invalid_targets() {
+    // Targets which can't be parsed give the column of the problem instead of a response
+    let cases = [
+        ("Int ->", 4, "Missing the type after `->`"),
+        ("Maybe (", 6, "Unclosed `(`"),
+        ("Map Int String)", 14, "Unmatched `)`"),
+        ("[Int)", 4, "`)` doesn't close the `[` before it"),
+        ("=> Int", 0, "Missing the type before `=>`"),
+        ("  ", 0, "The target type is empty"),
+    ];
+
+    for (target, column, message) in cases {
+        let error = run_on_file(
+            "tests/input_files/simple.hs".into(),
+            target.into(),
+            &Options::default(),
+        )
+        .expect_err(target);
+        assert_eq!((error.column, error.message.as_str()), (column, message));
+        assert!(!error.hint.is_empty());
+    }
+
+    let error = run_on_project(
+        "tests/input_files/project".into(),
+        Some("Int ->".to_string()),
+        &Options::default(),
+    )
+    .unwrap_err();
+    assert_eq!(error.column, 4);
+}
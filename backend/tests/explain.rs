@@ -0,0 +1,81 @@
+// Tests explanations of why an alias does or doesn't replace a target
+
+use htar::{run_on_file, Explanation, Failure, Options};
+
+fn explain(target: &str, alias: &str) -> Vec<Explanation> {
+    let options = Options {
+        explain: Some(alias.to_string()),
+        ..Options::default()
+    };
+    let response = run_on_file(
+        "tests/input_files/explain.hs".into(),
+        target.into(),
+        &options,
+    )
+    .unwrap();
+    response.results[0].explanations.clone()
+}
+
+fn failure(target: &str, alias: &str) -> Option<Failure> {
+    let explanations = explain(target, alias);
+    assert_eq!(explanations.len(), 1);
+    explanations[0].failure.clone()
+}
+
+#[test]
+fn explains_matches() {
+    let explanations = explain("Map Int String", "Table");
+    assert_eq!(explanations[0].replaced_type.as_deref(), Some("Table"));
+    assert_eq!(explanations[0].failure, None);
+    assert!(explanations[0].location.is_some());
+}
+
+#[test]
+fn explains_failures() {
+    assert_eq!(
+        failure("Map Int String", "Missing"),
+        Some(Failure::NotInScope)
+    );
+
+    assert_eq!(
+        failure("[Int]", "Table"),
+        Some(Failure::StructureMismatch {
+            alias: "Map Int String".to_string(),
+            target: "[Int]".to_string(),
+        })
+    );
+
+    assert_eq!(
+        failure("Map Int Bool", "Table"),
+        Some(Failure::ConcreteMismatch {
+            alias: "String".to_string(),
+            target: "Bool".to_string(),
+        })
+    );
+
+    assert_eq!(
+        failure("(Int, Bool)", "Pair"),
+        Some(Failure::InconsistentBinding {
+            variable: "a".to_string(),
+            first: "Int".to_string(),
+            second: "Bool".to_string(),
+        })
+    );
+
+    assert!(matches!(
+        failure("Code Int", "Wrapped"),
+        Some(Failure::KindClash { param, bound, .. }) if param == "f" && bound == "Code"
+    ));
+
+    assert!(matches!(
+        failure("Int -> Int", "Identity"),
+        Some(Failure::ScopeProblem { variable, .. }) if variable == "a"
+    ));
+
+    assert_eq!(
+        failure("Int -> String", "Render"),
+        Some(Failure::MissingConstraint {
+            constraint: "Show Int".to_string(),
+        })
+    );
+}
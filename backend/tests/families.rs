@@ -0,0 +1,49 @@
+// Tests evaluating type families in the right hand side of aliases
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn closed_family() {
+    let test_case = TestCase {
+        path: "families.hs",
+        target: "Char",
+        result: vec!["Element"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "families.hs",
+        target: "Bool",
+        result: vec!["FirstOf"],
+    };
+    test_on_file(test_case);
+
+    // Evaluation is blocked until `c` is known
+    let test_case = TestCase {
+        path: "families.hs",
+        target: "[(Elem s, Int)]",
+        result: vec!["Pairs s"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn open_family() {
+    let test_case = TestCase {
+        path: "families.hs",
+        target: "String",
+        result: vec!["MapKey"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn associated_default() {
+    let test_case = TestCase {
+        path: "families.hs",
+        target: "Int",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
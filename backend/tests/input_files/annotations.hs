@@ -0,0 +1,23 @@
+{-# LANGUAGE ScopedTypeVariables, TypeApplications #-}
+module Annotations where
+
+import Data.Map (Map)
+import qualified Data.Map as Map
+
+type Code = String
+type Table = Map Int Code
+
+annotated :: Int
+annotated = Map.size (Map.empty :: Map Int Code)
+
+applied :: Int
+applied = Map.size (mempty @(Map Int Code))
+
+count :: Table -> Int
+count (m :: Map Int Code) = Map.size m
+
+local :: Int
+local = Map.size table
+  where
+    table :: Map Int Code
+    table = Map.empty
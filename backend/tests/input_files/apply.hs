@@ -0,0 +1,26 @@
+module Apply where
+
+import Data.Map (Map)
+
+type Table = Map Int String
+type Listing a = [a]
+
+-- | Looks up a name
+lookupName :: Map Int String -> Int -> Maybe String
+lookupName table key = undefined
+
+names :: Map Int String {- the table -} -> [String]
+names = undefined
+
+both :: (Map Int String, Map Int String) -> Int
+both = undefined
+
+data a --> b = Arrow
+
+type Handler = Int --> String
+
+handle :: Int --> String
+handle = undefined
+
+counts :: Maybe [Int] -> Int
+counts = undefined
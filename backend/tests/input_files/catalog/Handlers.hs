@@ -0,0 +1,6 @@
+module Handlers where
+
+import Types
+
+type Endo x = x -> x
+type Couple b = (b, b)
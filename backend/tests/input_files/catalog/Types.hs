@@ -0,0 +1,11 @@
+module Types where
+
+type TestAlias = Int
+type TestAliasDupe = Int
+
+type GFunctionBin a = a -> a
+type TestFunc = Int -> Int
+
+type Pair a = (a, a)
+type Twin a b = (a, b)
+type Swapped a b = (b, a)
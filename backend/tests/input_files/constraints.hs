@@ -0,0 +1,10 @@
+class MonadLogger m
+class PrettyShow a
+
+type MonadApp m = (MonadIO m, MonadReader Env m, MonadLogger m)
+type MonadShow m a = (Monad m, Show a)
+type Pretty a = PrettyShow a
+type Wrapped a = Maybe a
+
+type Renderable :: Type -> Constraint
+type Renderable a = (Render a, Show a)
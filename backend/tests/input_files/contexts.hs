@@ -0,0 +1,4 @@
+type Lens s a = forall f. Functor f => (a -> f a) -> s -> f s
+type Getter s a = forall f. (Contravariant f, Functor f) => (a -> f a) -> s -> f s
+type ShowFn a = Show a => a -> String
+type MapFn k v = Map k v -> String
@@ -0,0 +1,19 @@
+{-# LANGUAGE CPP #-}
+module Cpp where
+
+import Data.Map (Map)
+
+#if MIN_VERSION_base(4,12,0)
+type Handler a = a -> IO ()
+#else
+type Handler a = a -> IO Bool
+#endif
+
+#ifdef DEBUG
+type Logger = String -> IO ()
+#endif
+
+type Table = Map Int String
+
+lookupAll :: Map Int String -> [String]
+lookupAll = foldr (:) []
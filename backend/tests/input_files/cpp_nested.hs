@@ -0,0 +1,12 @@
+{-# LANGUAGE CPP #-}
+module CppNested where
+
+#if defined(OLD_BASE)
+type Result = Int -> Bool
+#else
+#if defined(DEBUG)
+type Result = Int -> IO Bool
+#else
+type Result = Int -> Maybe Bool
+#endif
+#endif
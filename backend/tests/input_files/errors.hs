@@ -0,0 +1,15 @@
+module Errors where
+
+import qualified Data.Map as Map
+import Data.Map (Map)
+
+type Table = Map Int String
+
+lookupName :: Map Int String -> Int -> Maybe String
+lookupName table key = Map.lookup key table
+
+insertName :: Int -> String -> Map Int String -> Map Int String ]
+insertName = Map.insert
+
+names :: Map Int String -> [String]
+names = Map.elems
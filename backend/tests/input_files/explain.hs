@@ -0,0 +1,14 @@
+{-# LANGUAGE KindSignatures, RankNTypes #-}
+module Explain where
+
+import Data.Kind (Type)
+import Data.Map (Map)
+
+data Box a = Box a
+data Code = Code
+
+type Table = Map Int String
+type Pair a = (a, a)
+type Wrapped (f :: Type -> Type) = f Int
+type Identity = forall a. a -> a
+type Render a = Show a => a -> String
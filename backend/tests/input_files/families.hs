@@ -0,0 +1,15 @@
+type family Elem c where
+  Elem [a] = a
+  Elem (Set a) = a
+
+type family Key m
+type instance Key (Map k v) = k
+
+class Container f where
+  type Item f
+  type Item f = Int
+
+type Element = Elem [Char]
+type FirstOf = Elem (Set Bool)
+type MapKey = Key (Map String Int)
+type Pairs c = [(Elem c, Int)]
@@ -0,0 +1,19 @@
+{-# LANGUAGE DataKinds, PolyKinds, StandaloneKindSignatures #-}
+module Kinds where
+
+import Data.Kind (Type)
+import GHC.TypeLits (Nat, Symbol)
+
+data Box a = Box a
+data Pair a b = Pair a b
+data Labelled s a = Labelled a
+data Vector n a = Vector [a]
+
+type Const (a :: Type) (b :: k) = a
+
+type Tagged :: Symbol -> Type -> Type
+type Tagged s a = Labelled s a
+
+type Sized (n :: Nat) = Vector n Int
+
+type Wrapped (f :: Type -> Type) = f Int
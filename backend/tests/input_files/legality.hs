@@ -0,0 +1,30 @@
+module Legality where
+
+import Data.Map (Map)
+
+type Lens s a = forall f. Functor f => (a -> f a) -> s -> f s
+type Table = Map Int String
+type Listing a = [a]
+type Pairs a = [(a, a)]
+type Apply f = f Int
+
+class Pretty a where
+  pretty :: a -> String
+
+instance Pretty (Map Int String) where
+  pretty = show
+
+instance Pretty [a] where
+  pretty = const "list"
+
+instance Pretty [(a, a)] where
+  pretty = const "pairs"
+
+over :: (forall f. Functor f => (Int -> f Int) -> Bool -> f Bool) -> Bool
+over l = True
+
+wrapped :: Pairs Int
+wrapped = []
+
+listed :: Maybe [Int] -> Int
+listed = length
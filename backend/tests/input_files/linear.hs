@@ -0,0 +1,7 @@
+{-# LANGUAGE LinearTypes #-}
+
+type LinearFn a b = a %1 -> b
+type PolyFn m a b = a %m -> b
+type PlainFn a b = a -> b
+type LinearPair a b = (a, a) %1
+  -> b
@@ -0,0 +1,13 @@
+module NearMiss where
+
+import qualified Data.Map as Map
+import Data.Text (Text)
+
+data LockerState = Taken | Free
+
+type LockerMap = Map.Map Int (LockerState, String)
+type Pair a = (a, a)
+type Code = Int
+
+lockers :: Map.Map Int (LockerState, Text)
+lockers = Map.empty
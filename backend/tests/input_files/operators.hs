@@ -0,0 +1,15 @@
+infixr 5 :+: -- sums
+infixl 6 :*:
+{-
+infixl 7 :+:
+-}
+
+type f ~> g = forall x. f x -> g x
+type Sum3 a b c = a :+: b :+: c
+type Prod3 a b c = a :*: b :*: c
+type SumProd a b c = a :+: b :*: c
+type Ticked a b = a `Either` b
+type Prod a b = a :*: b
+
+mixed :: Int :+: Char :*: Bool
+mixed = undefined
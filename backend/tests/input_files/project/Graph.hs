@@ -0,0 +1,7 @@
+module Graph where
+
+import Node
+
+data Graph = Graph [Node]
+
+type Edge = (Int, Int)
@@ -0,0 +1,6 @@
+module Greeting where
+
+import Str
+
+name :: String
+name = "world"
@@ -0,0 +1,13 @@
+module Node where
+
+import {-# SOURCE #-} Graph (Edge)
+import qualified Types as T
+import Data.Map (Map)
+
+data Node = Node
+
+edges :: Node -> [(Int, Int)]
+edges _ = []
+
+labels :: Map Int String -> Node
+labels _ = Node
@@ -0,0 +1,6 @@
+module Str where
+
+type Str = String
+
+empty :: Str
+empty = ""
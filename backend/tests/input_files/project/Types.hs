@@ -0,0 +1,7 @@
+module Types where
+
+import Data.Map (Map)
+
+type Table = Map Int String
+
+type Names = [String]
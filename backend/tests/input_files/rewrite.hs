@@ -0,0 +1,19 @@
+module Rewrite where
+
+import qualified Data.Map as Map
+
+data LockerState = Taken | Free deriving (Show, Eq)
+
+type LockerMap = Map.Map Int (LockerState, String)
+type LookupReturn = Either String String
+type Locker = (LockerState, String)
+type Codes a = [(Int, a)]
+
+lockerLookup :: Int -> Map.Map Int (LockerState, String) -> Either String String
+lockerLookup = undefined
+
+lockerStates :: [(LockerState, String)] -> Int
+lockerStates = undefined
+
+lockerCodes :: Maybe [(Int, String)] -> Int
+lockerCodes = undefined
@@ -0,0 +1,24 @@
+module Scan where
+
+import Data.Map (Map)
+
+type Name = String
+type Tags = [String]
+type Lookup k = Map k Int
+
+data User = User { userName :: String, userTags :: [String] }
+
+data Entry = Entry !(Map Char Int) {-# UNPACK #-} !Int
+
+newtype Index = Index { getIndex :: Map Name Int }
+
+newtype Counts = Counts (Map Bool Int)
+
+data Expr a where
+  Lit :: Map Double Int -> Expr Int
+
+class Store s where
+  fetch :: s -> [String]
+
+lookupName :: Lookup Name -> Map Name Int
+lookupName = id
@@ -0,0 +1,10 @@
+module Score where
+
+import Data.Map (Map)
+
+type Table = Map Int String
+type Dict k = Map k String
+type Assoc k v = Map k v
+
+lookupName :: Map Int String -> Int -> Maybe String
+lookupName table key = undefined
@@ -0,0 +1,5 @@
+type UserAPI = "users" :> Capture "id" Int :> Get '[JSON] User
+type Endpoint path a = path :> Get '[JSON] a
+type Listed a = Get '[JSON] [a]
+type Flagged = Header "X-Flag" 'True
+type Sized = Vector 3 Int
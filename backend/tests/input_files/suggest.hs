@@ -0,0 +1,21 @@
+module Suggest where
+
+import qualified Data.Map as Map
+
+data LockerState = Taken | Free
+
+type Code = String
+
+lockerLookup :: Int -> Map.Map Int (LockerState, Code) -> Either String Code
+lockerLookup = undefined
+
+openLocker :: Int -> Map.Map Int (LockerState, Code) -> Map.Map Int (LockerState, Code)
+openLocker = undefined
+
+closeLocker :: Int -> Map.Map Int (LockerState, Code) -> Map.Map Int (LockerState, Code)
+closeLocker = undefined
+
+lookupAll :: Ord k => [k] -> Map.Map k (Either String [v]) -> [v]
+findAll :: Ord a => [a] -> Map.Map a (Either String [b]) -> [b]
+keysOf :: Map.Map key (Either String [val]) -> [key]
+sizeOf :: Map.Map x (Either String [y]) -> Int
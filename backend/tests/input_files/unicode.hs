@@ -0,0 +1,4 @@
+{-# LANGUAGE UnicodeSyntax #-}
+
+type Handler a = a → IO ()
+type Lens s a = ∀ f. Functor f ⇒ (a → f a) → s → f s
@@ -0,0 +1,3 @@
+module Api (module Handlers) where
+
+import Handlers
@@ -0,0 +1,20 @@
+module Handlers where
+
+import qualified Types as T
+
+data Handler = Handler { handlerKey :: T.Key }
+
+type Payload = [T.Row]
+type Result = Either String Payload
+type Count = Int
+type Orphan = Maybe Int
+
+run :: Result -> IO ()
+run _ = print (0 :: Count)
+
+data a --> b = Arrow
+type Code = Int
+type Hook = Int --> Code
+
+hook :: Hook
+hook = Arrow
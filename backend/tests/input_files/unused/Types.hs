@@ -0,0 +1,17 @@
+module Types
+  ( Row   -- exported, so it counts as used
+  , Key
+  , findRow
+  ) where
+
+import Data.Map (Map)
+
+-- | Cell is only mentioned in this comment
+type Cell = String
+type Row = (Int, String)
+type Key = Int
+type Index = Int
+type Rows = Map Index Row
+
+findRow :: Int -> Maybe Row
+findRow _ = Nothing
@@ -0,0 +1,66 @@
+// Tests that substituted types must have the kinds declared for alias parameters
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn kind_annotated_binders() {
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Int",
+        result: vec!["Const Int b"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Maybe",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Vector 3 Int",
+        result: vec!["Sized 3"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Vector \"3\" Int",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Box Int",
+        result: vec!["Wrapped Box"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Pair Int",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn standalone_kind_signatures() {
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Labelled \"name\" Int",
+        result: vec!["Tagged \"name\" Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "kinds.hs",
+        target: "Labelled Int Int",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
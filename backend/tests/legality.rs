@@ -0,0 +1,69 @@
+// Tests that replacements are checked against the extensions enabled for a module
+
+mod common;
+use common::{test_scan_with, ScanCase};
+use htar::Match;
+
+fn legality(m: &Match) -> (String, String, String) {
+    // Each scanned type with its replacement and whether GHC would accept it
+    (
+        m.occurrence.clone().unwrap().text,
+        m.replaced_type.clone(),
+        format!("{:?}", m.legality),
+    )
+}
+
+#[test]
+fn missing_extensions() {
+    let test_case = ScanCase {
+        path: "legality.hs",
+        result: vec![
+            (
+                "Map Int String",
+                "Table",
+                "NeedsExtensions([\"FlexibleInstances\"])",
+            ),
+            (
+                "Pairs Int",
+                "Apply Pairs",
+                "NeedsExtensions([\"LiberalTypeSynonyms\"])",
+            ),
+            (
+                "[(a, a)]",
+                "(Pairs a)",
+                "NeedsExtensions([\"FlexibleInstances\"])",
+            ),
+            ("[Int]", "(Listing Int)", "Legal"),
+            (
+                "[a]",
+                "(Listing a)",
+                "NeedsExtensions([\"TypeSynonymInstances\"])",
+            ),
+            ("f Int", "Apply f", "Legal"),
+            (
+                "forall f. Functor f => (Int -> f Int) -> Bool -> f Bool",
+                "Lens Bool Int",
+                "NeedsExtensions([\"RankNTypes\"])",
+            ),
+        ],
+    };
+    test_scan_with(test_case, legality);
+}
+
+#[test]
+fn pragma_and_cabal_extensions() {
+    let test_case = ScanCase {
+        path: "package/Package.hs",
+        result: vec![
+            ("Map Int String", "Table", "Legal"),
+            ("Pairs Int", "Apply Pairs", "Legal"),
+            ("f Int", "Apply f", "Legal"),
+            (
+                "forall f. Functor f => (Int -> f Int) -> Bool -> f Bool",
+                "Lens Bool Int",
+                "Legal",
+            ),
+        ],
+    };
+    test_scan_with(test_case, legality);
+}
@@ -0,0 +1,66 @@
+// Tests matching linear and multiplicity polymorphic arrows
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn linear_arrow() {
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int %1 -> Char",
+        result: vec!["LinearFn Int Char", "PolyFn One Int Char"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int ⊸ Char",
+        result: vec!["LinearFn Int Char", "PolyFn One Int Char"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn unrestricted_arrow() {
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int -> Char",
+        result: vec!["PlainFn Int Char"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int %Many -> Char",
+        result: vec!["PlainFn Int Char"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn polymorphic_arrow() {
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int %m -> Char",
+        result: vec!["PolyFn m Int Char"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn multiline_arrow() {
+    // A multiplicity can be on an earlier line than its arrow
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "(Int, Int) %1 -> Char",
+        result: vec!["LinearPair Int Char"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "linear.hs",
+        target: "Int %1\n  -> Char",
+        result: vec!["LinearFn Int Char", "PolyFn One Int Char"],
+    };
+    test_on_file(test_case);
+}
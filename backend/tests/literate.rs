@@ -0,0 +1,64 @@
+// Tests reading literate Haskell files and reporting positions within them
+
+mod common;
+use common::{test_on_file, test_scan_on_file, ScanCase, TestCase};
+use htar::{fix_file, scan_file, Options};
+
+#[test]
+fn bird_tracks() {
+    let test_case = TestCase {
+        path: "bird.lhs",
+        target: "Int -> IO ()",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = ScanCase {
+        path: "bird.lhs",
+        result: vec![("String -> IO ()", "Handler String", "Signature")],
+    };
+    test_scan_on_file(test_case);
+}
+
+#[test]
+fn latex_code_blocks() {
+    let test_case = TestCase {
+        path: "latex.lhs",
+        target: "Int -> IO ()",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = ScanCase {
+        path: "latex.lhs",
+        result: vec![("String -> IO ()", "Handler String", "Signature")],
+    };
+    test_scan_on_file(test_case);
+}
+
+#[test]
+fn literate_positions() {
+    // Rows and columns are those of the literate file
+    let response = scan_file("tests/input_files/bird.lhs".into(), &Options::default());
+    let location = response.matches[0].occurrence.clone().unwrap().location;
+    assert_eq!((location.start.row, location.start.col), (9, 11));
+    assert_eq!((location.end.row, location.end.col), (9, 26));
+
+    let response = scan_file("tests/input_files/latex.lhs".into(), &Options::default());
+    let location = response.matches[0].occurrence.clone().unwrap().location;
+    assert_eq!((location.start.row, location.start.col), (12, 9));
+}
+
+#[test]
+fn literate_fix() {
+    // A replaced type spanning bird track lines is written on the first of them
+    let applied = fix_file(
+        "tests/input_files/bird_apply.lhs".into(),
+        &Options::default(),
+    );
+    assert_eq!(applied.applied.len(), 1);
+    assert!(applied.skipped.is_empty());
+    assert!(applied
+        .source
+        .ends_with("\n> greet :: Handler String\n> greet = putStrLn\n"));
+}
@@ -0,0 +1,60 @@
+// Tests reporting aliases which differ from the target in only a few positions
+
+use htar::{run_on_file, Difference, NearMiss, Options};
+
+fn near_misses(target: &str, distance: usize) -> Vec<NearMiss> {
+    let options = Options {
+        near_miss: Some(distance),
+        ..Options::default()
+    };
+    let response = run_on_file(
+        "tests/input_files/near_miss.hs".into(),
+        target.into(),
+        &options,
+    )
+    .unwrap();
+    response.results[0].near_misses.clone()
+}
+
+#[test]
+fn names_differences() {
+    let found = near_misses("Map.Map Int (LockerState, Text)", 1);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].alias, "LockerMap");
+    assert_eq!(found[0].distance, 1);
+    assert_eq!(
+        found[0].differences,
+        vec![Difference {
+            alias: "String".to_string(),
+            target: "Text".to_string(),
+            column: 26,
+        }]
+    );
+
+    let found = near_misses("(Int, Bool)", 1);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].alias, "Pair");
+    assert_eq!(found[0].differences[0].alias, "a");
+    assert_eq!(found[0].differences[0].target, "Bool");
+}
+
+#[test]
+fn respects_distance() {
+    assert!(near_misses("Map.Map Bool (LockerState, Text)", 1).is_empty());
+    assert_eq!(near_misses("Map.Map Bool (LockerState, Text)", 2).len(), 1);
+
+    // Exact matches and aliases with nothing in common with the target aren't near misses
+    let options = Options {
+        near_miss: Some(3),
+        ..Options::default()
+    };
+    let response = run_on_file(
+        "tests/input_files/near_miss.hs".into(),
+        "Map.Map Int (LockerState, String)".into(),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(response.matches.len(), 1);
+    assert!(response.results[0].near_misses.is_empty());
+    assert!(near_misses("Bool", 3).is_empty());
+}
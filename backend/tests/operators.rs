@@ -0,0 +1,72 @@
+// Tests matching infix type operators
+
+mod common;
+use common::{test_on_file, test_scan_on_file, ScanCase, TestCase};
+
+#[test]
+fn operator_chain() {
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Int :+: Char :+: Bool",
+        result: vec!["Sum3 Int Char Bool"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Int :*: Char :*: Bool",
+        result: vec!["Prod3 Int Char Bool"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Int :+: Char :*: Bool",
+        result: vec!["SumProd Int Char Bool"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Int :*: Char :+: Bool",
+        result: vec![],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn operator_ticked() {
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Int `Either` Char",
+        result: vec!["Ticked Int Char"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn operator_alias() {
+    let test_case = TestCase {
+        path: "operators.hs",
+        target: "Maybe a -> List a",
+        result: vec!["Maybe ~> List"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn operator_commented_fixity() {
+    // Fixity declarations in comments are ignored, as are comments after a declaration
+    let test_case = ScanCase {
+        path: "operators.hs",
+        result: vec![
+            ("Char :*: Bool", "Prod Char Bool", "Signature"),
+            (
+                "Int :+: Char :*: Bool",
+                "SumProd Int Char Bool",
+                "Signature",
+            ),
+        ],
+    };
+    test_scan_on_file(test_case);
+}
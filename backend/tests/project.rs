@@ -0,0 +1,33 @@
+// Tests analysing a project whose modules import aliases from each other, from boot files and
+// from Backpack signatures
+
+mod common;
+use common::{test_project, ScanCase};
+
+#[test]
+fn project_scan() {
+    let test_case = ScanCase {
+        path: "project",
+        result: vec![
+            ("Greeting", "Str", "Str.hsig -> Str.hs"),
+            ("Node", "Edge", "Graph.hs-boot -> Graph.hs"),
+            ("Node", "T.Table", "Types.hs"),
+            ("Report", "T.Names", "Types.hs"),
+            ("Report", "Table", "Types.hs"),
+        ],
+    };
+    test_project(test_case, None);
+}
+
+#[test]
+fn project_target() {
+    let test_case = ScanCase {
+        path: "project",
+        result: vec![
+            ("Node", "T.Table", "Types.hs"),
+            ("Report", "Table", "Types.hs"),
+            ("Types", "Table", ""),
+        ],
+    };
+    test_project(test_case, Some("Map Int String"));
+}
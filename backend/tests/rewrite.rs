@@ -0,0 +1,45 @@
+// Tests rewriting whole signatures with several aliases at once
+
+use htar::{scan_file, Options};
+
+#[test]
+fn whole_signature() {
+    let response = scan_file("tests/input_files/rewrite.hs".into(), &Options::default());
+    let rewrites: Vec<(String, String, Vec<String>)> = response
+        .rewrites
+        .into_iter()
+        .map(|r| {
+            let edits = r.edits.into_iter().map(|m| m.replaced_type).collect();
+            (r.occurrence.text, r.rewritten, edits)
+        })
+        .collect();
+
+    // `Locker` overlaps the larger replacement `LockerMap` so isn't used in the first signature
+    let expected = vec![
+        (
+            "Int -> Map.Map Int (LockerState, String) -> Either String String",
+            "Int -> LockerMap -> LookupReturn",
+            vec!["LockerMap", "LookupReturn"],
+        ),
+        (
+            "[(LockerState, String)] -> Int",
+            "[Locker] -> Int",
+            vec!["Locker"],
+        ),
+        // A type which is applied to keeps its replacement in parentheses
+        (
+            "Maybe [(Int, String)] -> Int",
+            "Maybe (Codes String) -> Int",
+            vec!["(Codes String)"],
+        ),
+    ];
+    let expected: Vec<(String, String, Vec<String>)> = expected
+        .into_iter()
+        .map(|(t, r, e)| {
+            let edits = e.into_iter().map(|e| e.to_string()).collect();
+            (t.to_string(), r.to_string(), edits)
+        })
+        .collect();
+
+    assert_eq!(rewrites, expected);
+}
@@ -0,0 +1,36 @@
+// Tests scanning a source file for types which could use its aliases
+
+mod common;
+use common::{test_scan_on_file, ScanCase};
+
+#[test]
+fn scan_declarations() {
+    let test_case = ScanCase {
+        path: "scan.hs",
+        result: vec![
+            ("Map Bool Int", "Lookup Bool", "ConstructorField"),
+            ("Map Char Int", "Lookup Char", "ConstructorField"),
+            ("Map Double Int", "Lookup Double", "GadtConstructor"),
+            ("Map Name Int", "Lookup Name", "RecordField"),
+            ("Map Name Int", "Lookup Name", "Signature"),
+            ("String", "Name", "RecordField"),
+            ("[String]", "Tags", "ClassMethod"),
+            ("[String]", "Tags", "RecordField"),
+        ],
+    };
+    test_scan_on_file(test_case);
+}
+
+#[test]
+fn scan_expressions() {
+    let test_case = ScanCase {
+        path: "annotations.hs",
+        result: vec![
+            ("Map Int Code", "Table", "ExpressionAnnotation"),
+            ("Map Int Code", "Table", "LocalSignature"),
+            ("Map Int Code", "Table", "PatternSignature"),
+            ("Map Int Code", "Table", "TypeApplication"),
+        ],
+    };
+    test_scan_on_file(test_case);
+}
@@ -0,0 +1,65 @@
+// Tests ranking matches and keeping only the best match for each location
+
+use htar::{run_on_file, scan_file, Match, Options};
+
+fn ranked(matches: &[Match]) -> Vec<String> {
+    // Replacements from the highest score to the lowest
+    let mut matches = matches.to_vec();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.into_iter().map(|m| m.replaced_type).collect()
+}
+
+#[test]
+fn ranking() {
+    let options = Options::default();
+    let response = run_on_file(
+        "tests/input_files/score.hs".into(),
+        "Map Int String".into(),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        ranked(&response.matches),
+        vec!["Table", "Dict Int", "Assoc Int String"]
+    );
+
+    // Aliases with free parameters are ranked lower
+    let response = run_on_file(
+        "tests/input_files/score.hs".into(),
+        "Map a String".into(),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(ranked(&response.matches), vec!["Dict a", "Assoc a String"]);
+}
+
+#[test]
+fn best() {
+    let options = Options {
+        best: true,
+        ..Options::default()
+    };
+    let response = run_on_file(
+        "tests/input_files/score.hs".into(),
+        "Map Int String".into(),
+        &options,
+    )
+    .unwrap();
+    let replaced: Vec<String> = response
+        .matches
+        .into_iter()
+        .map(|m| m.replaced_type)
+        .collect();
+    assert_eq!(replaced, vec!["Table"]);
+
+    let response = scan_file("tests/input_files/score.hs".into(), &options);
+    let replaced: Vec<(String, String)> = response
+        .matches
+        .into_iter()
+        .map(|m| (m.occurrence.unwrap().text, m.replaced_type))
+        .collect();
+    assert_eq!(
+        replaced,
+        vec![("Map Int String".to_string(), "Table".to_string())]
+    );
+}
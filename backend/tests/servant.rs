@@ -0,0 +1,62 @@
+// Tests matching type level literals and promoted constructors
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn servant_literal() {
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "\"users\" :> Capture \"id\" Int :> Get '[JSON] User",
+        result: vec!["UserAPI"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "\"posts\" :> Capture \"id\" Int :> Get '[JSON] User",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "Vector 3 Int",
+        result: vec!["Sized"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn servant_literal_variable() {
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "\"health\" :> Get '[JSON] Status",
+        result: vec!["Endpoint \"health\" Status"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn servant_promoted() {
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "Get '[JSON] [User]",
+        result: vec!["Listed User"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "Get [JSON] [User]",
+        result: vec![],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "servant.hs",
+        target: "Header \"X-Flag\" True",
+        result: vec!["Flagged"],
+    };
+    test_on_file(test_case);
+}
@@ -0,0 +1,44 @@
+// Tests suggesting new aliases for types which are written out repeatedly
+
+use htar::{suggest_path, Options};
+
+#[test]
+fn suggests_repeated_types() {
+    let response = suggest_path("tests/input_files/suggest.hs".into(), &Options::default());
+    let suggestion = &response.suggestions[0];
+
+    assert_eq!(
+        suggestion.declaration,
+        "type NewAlias1 = Map.Map Int (LockerState, Code)"
+    );
+    assert_eq!(suggestion.characters_saved, 61);
+    let rows: Vec<usize> = suggestion
+        .occurrences
+        .iter()
+        .map(|o| o.occurrence.location.start.row)
+        .collect();
+    assert_eq!(rows, vec![8, 11, 11, 14, 14]);
+
+    // Types only written inside a suggested type aren't suggested again
+    assert!(response
+        .suggestions
+        .iter()
+        .all(|s| !s.declaration.ends_with("= (LockerState, Code)")));
+}
+
+#[test]
+fn renames_type_variables() {
+    // Types which only differ in the names of their type variables are the same type
+    let response = suggest_path("tests/input_files/suggest.hs".into(), &Options::default());
+    let suggestion = response
+        .suggestions
+        .iter()
+        .find(|s| {
+            s.declaration
+                .ends_with(" k v = Map.Map k (Either String [v])")
+        })
+        .unwrap();
+
+    assert_eq!(suggestion.occurrences.len(), 4);
+    assert!(suggestion.characters_saved > 0);
+}
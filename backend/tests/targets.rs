@@ -0,0 +1,84 @@
+// Tests requests with several target types, which are grouped per target in the response
+
+use htar::{alias_replacement, run_on_file, run_targets_on_file, Match, Options, RequestAlias};
+
+fn replaced_types(matches: &[Match]) -> Vec<String> {
+    let mut replaced: Vec<String> = matches.iter().map(|m| m.replaced_type.clone()).collect();
+    replaced.sort();
+    replaced
+}
+
+#[test]
+fn grouped_per_target() {
+    let targets = ["Bool", "Maybe String", "Double", "(Int, Char)"];
+    let response = run_targets_on_file(
+        "tests/input_files/concrete.hs".into(),
+        targets.iter().map(|t| t.to_string()).collect(),
+        &Options::default(),
+    )
+    .unwrap();
+
+    let results: Vec<(String, Vec<String>)> = response
+        .results
+        .iter()
+        .map(|r| (r.target_type.clone(), replaced_types(&r.matches)))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            ("Bool".to_string(), vec!["CBool".to_string()]),
+            ("Maybe String".to_string(), vec!["CMaybe".to_string()]),
+            ("Double".to_string(), vec![]),
+            ("(Int, Char)".to_string(), vec!["C2Tuple".to_string()]),
+        ]
+    );
+    assert!(response.matches.is_empty());
+}
+
+#[test]
+fn target_type_kept() {
+    // `target_type` is matched first and its matches are also given in `matches`
+    let request = RequestAlias {
+        target_type: "Int".to_string(),
+        targets: vec!["Char".to_string(), "Int".to_string()],
+        source: "type CInt = Int\ntype CChar = Char\n".to_string(),
+        default_extensions: Vec::new(),
+        literate: false,
+        defines: None,
+        best: false,
+        explain: None,
+        near_miss: None,
+    };
+    let response = alias_replacement(request).unwrap();
+
+    assert_eq!(replaced_types(&response.matches), vec!["CInt"]);
+    let targets: Vec<&str> = response
+        .results
+        .iter()
+        .map(|r| r.target_type.as_str())
+        .collect();
+    assert_eq!(targets, vec!["Int", "Char"]);
+
+    let single = run_on_file(
+        "tests/input_files/concrete.hs".into(),
+        "Int".into(),
+        &Options::default(),
+    )
+    .unwrap();
+    assert_eq!(single.results.len(), 1);
+    assert_eq!(
+        replaced_types(&single.matches),
+        replaced_types(&single.results[0].matches)
+    );
+}
+
+#[test]
+fn invalid_target_in_batch() {
+    let error = run_targets_on_file(
+        "tests/input_files/concrete.hs".into(),
+        vec!["Int".to_string(), "Maybe (".to_string()],
+        &Options::default(),
+    )
+    .unwrap_err();
+    assert_eq!(error.target_type, "Maybe (");
+}
@@ -0,0 +1,45 @@
+// Tests targets, sources and replacements using UnicodeSyntax
+
+mod common;
+use common::{test_on_file, TestCase};
+
+#[test]
+fn unicode_source() {
+    let test_case = TestCase {
+        path: "unicode.hs",
+        target: "Int -> IO ()",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "unicode.hs",
+        target: "Int → IO ()",
+        result: vec!["Handler Int"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "unicode.hs",
+        target: "(Show a, Functor f) => (Int -> f Int) -> Person -> f Person",
+        result: vec!["Show a ⇒ Lens Person Int"],
+    };
+    test_on_file(test_case);
+}
+
+#[test]
+fn unicode_target() {
+    let test_case = TestCase {
+        path: "generic.hs",
+        target: "String → String",
+        result: vec!["GFunctionBin String", "GFunctionBinMixed String String"],
+    };
+    test_on_file(test_case);
+
+    let test_case = TestCase {
+        path: "contexts.hs",
+        target: "(Show a, Eq a) ⇒ a → String",
+        result: vec!["Eq a => ShowFn a"],
+    };
+    test_on_file(test_case);
+}
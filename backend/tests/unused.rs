@@ -0,0 +1,37 @@
+// Tests finding the aliases of a project which are never referenced
+
+use htar::{unused_path, Options};
+
+#[test]
+fn unused_aliases() {
+    let response = unused_path("tests/input_files/unused".into(), &Options::default());
+    let unused: Vec<(String, String, usize, Vec<String>)> = response
+        .unused
+        .iter()
+        .map(|u| {
+            (
+                u.alias.module.clone(),
+                u.alias.alias.clone(),
+                u.alias.location.start.row,
+                u.exported_by.clone(),
+            )
+        })
+        .collect();
+
+    // Aliases used by signatures, annotations, other aliases, data fields and export lists
+    // aren't reported. Mentions in comments aren't references, but those after an operator
+    // containing `--` are
+    assert_eq!(
+        unused,
+        vec![
+            (
+                "Handlers".to_string(),
+                "Orphan".to_string(),
+                9,
+                vec!["Api".to_string(), "Handlers".to_string()]
+            ),
+            ("Types".to_string(), "Cell".to_string(), 9, vec![]),
+            ("Types".to_string(), "Rows".to_string(), 13, vec![]),
+        ]
+    );
+}